@@ -1,4 +1,5 @@
-extern crate embed_resource;
+use win_task_dialog::build::{compile, ManifestOptions};
+
 fn main() {
-    embed_resource::compile("example-manifest.rc", embed_resource::NONE);
+    compile(&ManifestOptions::from_env()).unwrap();
 }
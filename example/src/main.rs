@@ -24,6 +24,12 @@ unsafe extern "system" fn callback(
 }
 
 fn main() {
+    install_panic_dialog(PanicDialogOptions {
+        issue_tracker_url: "https://github.com/RobinCodeX/win-task-dialog-for-rust/issues"
+            .to_string(),
+        ..Default::default()
+    });
+
     let mut conf = TaskDialogConfig {
         flags: TDF_USE_COMMAND_LINKS | TDF_ENABLE_HYPERLINKS,
         common_buttons: TDCBF_OK_BUTTON | TDCBF_CANCEL_BUTTON,
@@ -34,23 +40,25 @@ fn main() {
         footer: "footer\n<a href=\"http://example.com\">example.com</a>".to_string(),
         buttons: vec![
             TaskDialogButton {
-                id: 10,
-                text: "One".to_string(),
+                note: "Command link note".to_string(),
+                ..TaskDialogButton::new(10, "One")
             },
             TaskDialogButton {
-                id: 11,
-                text: "贰".to_string(),
+                elevation_required: true,
+                is_default: true,
+                ..TaskDialogButton::new(11, "贰")
             },
-        ],
-        default_button: 11,
-        radio_buttons: vec![
             TaskDialogButton {
-                id: 10,
-                text: "Option 1".to_string(),
+                enabled: false,
+                ..TaskDialogButton::new(12, "Disabled")
             },
+        ],
+        radio_buttons: vec![
+            TaskDialogButton::new(10, "Option 1"),
+            TaskDialogButton::new(11, "Option 2"),
             TaskDialogButton {
-                id: 11,
-                text: "Option 2".to_string(),
+                enabled: false,
+                ..TaskDialogButton::new(12, "Option 3 (disabled)")
             },
         ],
         main_icon: TD_SHIELD_ICON,
@@ -73,7 +81,18 @@ fn main() {
     show_msg_dialog("Title", "Hi", "Info", TD_INFORMATION_ICON);
     show_msg_dialog("Title", "!!!", "Error", TD_ERROR_ICON);
 
+    set_dialog_defaults(DialogDefaults {
+        window_title: "Presets".to_string(),
+        ..Default::default()
+    });
+    if confirm("", "Show the error details dialog?") {
+        let err = std::fs::read("missing.txt").unwrap_err();
+        error_details(&err);
+    }
+
     page_navigation();
+    button_actions();
+    spawned_dialog();
 }
 
 // Show dynamic text dialog
@@ -83,7 +102,13 @@ fn show_dynamic_text_dialog() {
         main_instruction: "Main".to_string(),
         content: "Content".to_string(),
         footer: "Footer".to_string(),
-        expanded_information: "Info".to_string(),
+        expander: Some(Expander {
+            text: "Info".to_string(),
+            expanded_label: "Hide details".to_string(),
+            collapsed_label: "Show details".to_string(),
+            placement: ExpanderPlacement::Footer,
+            ..Default::default()
+        }),
         ..Default::default()
     };
 
@@ -104,7 +129,8 @@ fn show_dynamic_text_dialog() {
         (*conf).set_expanded_information("Info !!!");
     });
 
-    show_task_dialog(&mut conf).unwrap();
+    let result = show_task_dialog(&mut conf).unwrap();
+    println!("expanded: {}", result.expanded);
 }
 
 // Process Bar Marquee
@@ -112,6 +138,7 @@ fn show_process_bar_marquee() {
     let mut conf = TaskDialogConfig {
         window_title: "Process Bar".to_string(),
         main_instruction: "Process Bar Marquee 1".to_string(),
+        main_icon: TD_INFORMATION_ICON,
         ..Default::default()
     };
     conf.enable_process_bar(true);
@@ -127,6 +154,7 @@ fn show_process_bar_marquee() {
         (*conf).set_process_bar_marquee(true, 1);
         thread::sleep(Duration::from_secs(3));
         (*conf).set_main_instruction("Process Bar Marquee Stop");
+        (*conf).set_main_icon(TD_WARNING_ICON).ok();
         (*conf).set_process_bar_marquee(false, 1);
     });
 
@@ -143,20 +171,19 @@ fn show_process_bar() {
     };
     conf.enable_process_bar(false);
 
-    let conf_ptr: *mut TaskDialogConfig = &mut conf;
-    let conf_long_ptr = conf_ptr as isize;
-    thread::spawn(move || unsafe {
-        let conf = std::mem::transmute::<isize, *mut TaskDialogConfig>(conf_long_ptr);
-        for i in 0..101 {
-            if (*conf).is_destroyed {
-                return;
+    let outcome = ProgressDialog::run(conf, |progress, cancel| {
+        progress.set_length(Some(100));
+        for i in 1..=100 {
+            if cancel.is_cancelled() {
+                break;
             }
             thread::sleep(Duration::from_millis(20));
-            (*conf).set_process_bar(i);
+            progress.set_message(&format!("Step {}", i));
+            progress.inc(1);
         }
-    });
-
-    show_task_dialog(&mut conf).unwrap();
+    })
+    .unwrap();
+    println!("process_bar: {:?}", outcome);
 }
 
 fn page_navigation() {
@@ -177,19 +204,13 @@ fn page_navigation() {
             if w_param.0 == 1776 {
                 (*ref_data).window_title = "Page #1".to_owned();
                 (*ref_data).main_instruction = "Page #1".to_owned();
-                (*ref_data).buttons = vec![TaskDialogButton {
-                    id: 1777,
-                    text: "Continue".to_owned(),
-                }];
+                (*ref_data).buttons = vec![TaskDialogButton::new(1777, "Continue")];
                 (*ref_data).navigate_page(&mut *ref_data);
                 return S_FALSE;
             } else if w_param.0 == 1777 {
                 (*ref_data).window_title = "Page #2".to_owned();
                 (*ref_data).main_instruction = "Page #2".to_owned();
-                (*ref_data).buttons = vec![TaskDialogButton {
-                    id: 1776,
-                    text: "Back to page #1".to_owned(),
-                }];
+                (*ref_data).buttons = vec![TaskDialogButton::new(1776, "Back to page #1")];
                 (*ref_data).navigate_page(&mut *ref_data);
                 return S_FALSE;
             }
@@ -203,12 +224,65 @@ fn page_navigation() {
         main_instruction: "Page #1".to_owned(),
         callback: Some(page1_callback),
         common_buttons: TDCBF_CLOSE_BUTTON,
-        buttons: vec![TaskDialogButton {
-            id: 1777,
-            text: "Continue".to_owned(),
-        }],
+        buttons: vec![TaskDialogButton::new(1777, "Continue")],
         ..Default::default()
     };
 
     show_task_dialog(&mut conf).unwrap();
 }
+
+fn button_actions() {
+    fn count(conf: &mut TaskDialogConfig) {
+        let clicks = conf.content.len() - "Clicks: ".len() + 1;
+        conf.set_content(&format!("Clicks: {}", "I".repeat(clicks)));
+    }
+
+    let mut conf = TaskDialogConfig {
+        window_title: "Button Actions".to_owned(),
+        main_instruction: "Buttons with actions".to_owned(),
+        content: "Clicks: ".to_owned(),
+        common_buttons: TDCBF_CLOSE_BUTTON,
+        buttons: vec![
+            TaskDialogButton {
+                action: ButtonAction::keep_open(count),
+                ..TaskDialogButton::new(2001, "Count")
+            },
+            TaskDialogButton {
+                action: ButtonAction::Navigate(Box::new(TaskDialogConfig {
+                    window_title: "Button Actions".to_owned(),
+                    main_instruction: "Page #2".to_owned(),
+                    buttons: vec![TaskDialogButton {
+                        action: ButtonAction::CloseWith(IDOK),
+                        ..TaskDialogButton::new(2003, "Done")
+                    }],
+                    ..Default::default()
+                })),
+                ..TaskDialogButton::new(2002, "Next page")
+            },
+        ],
+        ..Default::default()
+    };
+
+    let result = show_task_dialog(&mut conf).unwrap();
+    println!("button_actions: {}", result.button_id);
+}
+
+fn spawned_dialog() {
+    let mut conf = TaskDialogConfig {
+        window_title: "Spawned Dialog".to_owned(),
+        main_instruction: "The dialog runs on its own thread".to_owned(),
+        common_buttons: TDCBF_CANCEL_BUTTON,
+        ..Default::default()
+    };
+    conf.enable_process_bar(false);
+
+    let dialog = spawn_task_dialog(conf).unwrap();
+    for i in 1..=100 {
+        dialog.handle().set_process_bar(i);
+        dialog.handle().set_content(&format!("{}%", i));
+        thread::sleep(Duration::from_millis(30));
+    }
+    dialog.close(IDOK);
+    let result = dialog.join().unwrap();
+    println!("spawned_dialog: {}", result.button_id);
+}
@@ -0,0 +1,28 @@
+use crate::*;
+use std::io::Error;
+
+/// Shows a dialog and returns the result, the helpers built on a backend
+/// can be driven without a desktop, e.g. by a closure in tests. The
+/// helpers taking a backend take it as their first argument.
+pub trait DialogBackend {
+    fn show(&mut self, conf: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error>;
+}
+
+/// Shows the dialogs with `show_task_dialog`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeBackend;
+
+impl DialogBackend for NativeBackend {
+    fn show(&mut self, conf: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error> {
+        show_task_dialog(conf)
+    }
+}
+
+impl<F> DialogBackend for F
+where
+    F: FnMut(&mut TaskDialogConfig) -> Result<TaskDialogResult, Error>,
+{
+    fn show(&mut self, conf: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error> {
+        self(conf)
+    }
+}
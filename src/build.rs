@@ -0,0 +1,361 @@
+//! Generate the application manifest and the resource script which embeds it,
+//! for use in `build.rs`.
+//!
+//! ```ignore
+//! // build.rs, with `win-task-dialog = { version = "1", features = ["build"] }`
+//! // in `[build-dependencies]`
+//! use win_task_dialog::build::{compile, ManifestOptions};
+//!
+//! fn main() {
+//!     compile(&ManifestOptions::from_env()).unwrap();
+//! }
+//! ```
+
+use std::fmt::Write;
+
+/// The DPI awareness of the application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DpiAwareness {
+    /// Windows scales the application as a bitmap.
+    Unaware,
+    System,
+    PerMonitor,
+    /// Falls back to `PerMonitor` before Windows 10 version 1703.
+    PerMonitorV2,
+}
+
+/// The `requestedExecutionLevel` of the application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionLevel {
+    AsInvoker,
+    HighestAvailable,
+    RequireAdministrator,
+}
+
+/// A Windows version in the `supportedOS` list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SupportedOs {
+    WindowsVista,
+    Windows7,
+    Windows8,
+    Windows81,
+    /// Windows 10 and Windows 11.
+    Windows10,
+}
+
+impl SupportedOs {
+    /// All the versions, from Windows Vista to Windows 10.
+    pub const ALL: [SupportedOs; 5] = [
+        SupportedOs::WindowsVista,
+        SupportedOs::Windows7,
+        SupportedOs::Windows8,
+        SupportedOs::Windows81,
+        SupportedOs::Windows10,
+    ];
+
+    /// The `supportedOS` ID of the version.
+    pub fn guid(self) -> &'static str {
+        match self {
+            SupportedOs::WindowsVista => "{e2011457-1546-43c5-a5fe-008deee3d3f0}",
+            SupportedOs::Windows7 => "{35138b9a-5d96-4fbd-8e2d-a2440225f93a}",
+            SupportedOs::Windows8 => "{4a2f28e3-53b9-4441-ba9c-d69d4a4a6e38}",
+            SupportedOs::Windows81 => "{1f676c76-80e1-4239-95bb-83d0f6d0da78}",
+            SupportedOs::Windows10 => "{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}",
+        }
+    }
+}
+
+/// The options of the application manifest.
+pub struct ManifestOptions {
+    /// The name of the `assemblyIdentity`, no `assemblyIdentity` when empty.
+    pub name: String,
+    /// A Cargo version such as `1.2.3`, written as `1.2.3.0`.
+    pub version: String,
+    pub description: String,
+    /// Depend on comctl32 version 6, required by `TaskDialogIndirect`.
+    pub common_controls_v6: bool,
+    pub dpi_awareness: DpiAwareness,
+    pub execution_level: ExecutionLevel,
+    pub ui_access: bool,
+    pub supported_os: Vec<SupportedOs>,
+    /// Allow paths longer than `MAX_PATH`, when enabled in the system settings.
+    pub long_path_aware: bool,
+}
+
+impl Default for ManifestOptions {
+    fn default() -> Self {
+        ManifestOptions {
+            name: "".to_string(),
+            version: "".to_string(),
+            description: "".to_string(),
+            common_controls_v6: true,
+            dpi_awareness: DpiAwareness::PerMonitorV2,
+            execution_level: ExecutionLevel::AsInvoker,
+            ui_access: false,
+            supported_os: SupportedOs::ALL.to_vec(),
+            long_path_aware: false,
+        }
+    }
+}
+
+impl ManifestOptions {
+    /// The default options with the name, version and description of the
+    /// `CARGO_PKG_*` variables of the build script.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        ManifestOptions {
+            name: var("CARGO_PKG_NAME"),
+            version: var("CARGO_PKG_VERSION"),
+            description: var("CARGO_PKG_DESCRIPTION"),
+            ..Default::default()
+        }
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Convert a Cargo version to the four numbers of a manifest version,
+/// the pre-release and build metadata are dropped.
+fn manifest_version(version: &str) -> String {
+    let version = version.split(['-', '+']).next().unwrap_or_default();
+    let mut numbers: Vec<u16> = version
+        .split('.')
+        .take(4)
+        .map(|number| number.parse().unwrap_or(0))
+        .collect();
+    numbers.resize(4, 0);
+    format!(
+        "{}.{}.{}.{}",
+        numbers[0], numbers[1], numbers[2], numbers[3]
+    )
+}
+
+/** Generate the application manifest.
+
+```
+use win_task_dialog::build::{manifest, ManifestOptions};
+
+assert_eq!(
+    manifest(&ManifestOptions::default()),
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+    <dependency>
+        <dependentAssembly>
+            <assemblyIdentity type="win32" name="Microsoft.Windows.Common-Controls" version="6.0.0.0" processorArchitecture="*" publicKeyToken="6595b64144ccf1df" language="*"/>
+        </dependentAssembly>
+    </dependency>
+    <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+        <security>
+            <requestedPrivileges>
+                <requestedExecutionLevel level="asInvoker" uiAccess="false"/>
+            </requestedPrivileges>
+        </security>
+    </trustInfo>
+    <compatibility xmlns="urn:schemas-microsoft-com:compatibility.v1">
+        <application>
+            <supportedOS Id="{e2011457-1546-43c5-a5fe-008deee3d3f0}"/>
+            <supportedOS Id="{35138b9a-5d96-4fbd-8e2d-a2440225f93a}"/>
+            <supportedOS Id="{4a2f28e3-53b9-4441-ba9c-d69d4a4a6e38}"/>
+            <supportedOS Id="{1f676c76-80e1-4239-95bb-83d0f6d0da78}"/>
+            <supportedOS Id="{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}"/>
+        </application>
+    </compatibility>
+    <application xmlns="urn:schemas-microsoft-com:asm.v3">
+        <windowsSettings>
+            <dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">true/pm</dpiAware>
+            <dpiAwareness xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">PerMonitorV2, PerMonitor</dpiAwareness>
+        </windowsSettings>
+    </application>
+</assembly>
+"#
+);
+```
+
+```
+use win_task_dialog::build::*;
+
+let options = ManifestOptions {
+    name: "my-app".to_owned(),
+    version: "1.2.3-beta.1".to_owned(),
+    description: "Tom & Jerry".to_owned(),
+    common_controls_v6: false,
+    dpi_awareness: DpiAwareness::System,
+    execution_level: ExecutionLevel::RequireAdministrator,
+    ui_access: true,
+    supported_os: vec![SupportedOs::Windows10],
+    long_path_aware: true,
+};
+assert_eq!(
+    manifest(&options),
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+    <assemblyIdentity type="win32" name="my-app" version="1.2.3.0" processorArchitecture="*"/>
+    <description>Tom &amp; Jerry</description>
+    <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+        <security>
+            <requestedPrivileges>
+                <requestedExecutionLevel level="requireAdministrator" uiAccess="true"/>
+            </requestedPrivileges>
+        </security>
+    </trustInfo>
+    <compatibility xmlns="urn:schemas-microsoft-com:compatibility.v1">
+        <application>
+            <supportedOS Id="{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}"/>
+        </application>
+    </compatibility>
+    <application xmlns="urn:schemas-microsoft-com:asm.v3">
+        <windowsSettings>
+            <dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">true</dpiAware>
+            <dpiAwareness xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">system</dpiAwareness>
+            <longPathAware xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">true</longPathAware>
+        </windowsSettings>
+    </application>
+</assembly>
+"#
+);
+```
+*/
+pub fn manifest(options: &ManifestOptions) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    xml.push_str("<assembly xmlns=\"urn:schemas-microsoft-com:asm.v1\" manifestVersion=\"1.0\">\n");
+
+    if !options.name.is_empty() {
+        writeln!(
+            xml,
+            "    <assemblyIdentity type=\"win32\" name=\"{}\" version=\"{}\" processorArchitecture=\"*\"/>",
+            escape_xml(&options.name),
+            manifest_version(&options.version)
+        )
+        .unwrap();
+    }
+    if !options.description.is_empty() {
+        writeln!(
+            xml,
+            "    <description>{}</description>",
+            escape_xml(&options.description)
+        )
+        .unwrap();
+    }
+
+    if options.common_controls_v6 {
+        xml.push_str(concat!(
+            "    <dependency>\n",
+            "        <dependentAssembly>\n",
+            "            <assemblyIdentity type=\"win32\" name=\"Microsoft.Windows.Common-Controls\"",
+            " version=\"6.0.0.0\" processorArchitecture=\"*\" publicKeyToken=\"6595b64144ccf1df\"",
+            " language=\"*\"/>\n",
+            "        </dependentAssembly>\n",
+            "    </dependency>\n",
+        ));
+    }
+
+    let level = match options.execution_level {
+        ExecutionLevel::AsInvoker => "asInvoker",
+        ExecutionLevel::HighestAvailable => "highestAvailable",
+        ExecutionLevel::RequireAdministrator => "requireAdministrator",
+    };
+    xml.push_str("    <trustInfo xmlns=\"urn:schemas-microsoft-com:asm.v3\">\n");
+    xml.push_str("        <security>\n");
+    xml.push_str("            <requestedPrivileges>\n");
+    writeln!(
+        xml,
+        "                <requestedExecutionLevel level=\"{}\" uiAccess=\"{}\"/>",
+        level, options.ui_access
+    )
+    .unwrap();
+    xml.push_str("            </requestedPrivileges>\n");
+    xml.push_str("        </security>\n");
+    xml.push_str("    </trustInfo>\n");
+
+    if !options.supported_os.is_empty() {
+        xml.push_str("    <compatibility xmlns=\"urn:schemas-microsoft-com:compatibility.v1\">\n");
+        xml.push_str("        <application>\n");
+        for os in options.supported_os.iter() {
+            writeln!(xml, "            <supportedOS Id=\"{}\"/>", os.guid()).unwrap();
+        }
+        xml.push_str("        </application>\n");
+        xml.push_str("    </compatibility>\n");
+    }
+
+    let dpi = match options.dpi_awareness {
+        DpiAwareness::Unaware => None,
+        DpiAwareness::System => Some(("true", "system")),
+        DpiAwareness::PerMonitor => Some(("true/pm", "PerMonitor")),
+        DpiAwareness::PerMonitorV2 => Some(("true/pm", "PerMonitorV2, PerMonitor")),
+    };
+    if dpi.is_some() || options.long_path_aware {
+        xml.push_str("    <application xmlns=\"urn:schemas-microsoft-com:asm.v3\">\n");
+        xml.push_str("        <windowsSettings>\n");
+        if let Some((dpi_aware, dpi_awareness)) = dpi {
+            writeln!(
+                xml,
+                "            <dpiAware xmlns=\"http://schemas.microsoft.com/SMI/2005/WindowsSettings\">{}</dpiAware>",
+                dpi_aware
+            )
+            .unwrap();
+            writeln!(
+                xml,
+                "            <dpiAwareness xmlns=\"http://schemas.microsoft.com/SMI/2016/WindowsSettings\">{}</dpiAwareness>",
+                dpi_awareness
+            )
+            .unwrap();
+        }
+        if options.long_path_aware {
+            xml.push_str("            <longPathAware xmlns=\"http://schemas.microsoft.com/SMI/2016/WindowsSettings\">true</longPathAware>\n");
+        }
+        xml.push_str("        </windowsSettings>\n");
+        xml.push_str("    </application>\n");
+    }
+
+    xml.push_str("</assembly>\n");
+    xml
+}
+
+/** Generate the resource script which embeds the manifest file as the
+application manifest.
+
+```
+use win_task_dialog::build::resource_script;
+
+assert_eq!(
+    resource_script(r#"C:\out\my "app".manifest"#),
+    "#define RT_MANIFEST 24\n1 RT_MANIFEST \"C:\\\\out\\\\my \"\"app\"\".manifest\"\n"
+);
+```
+*/
+pub fn resource_script(manifest_path: &str) -> String {
+    format!(
+        "#define RT_MANIFEST 24\n1 RT_MANIFEST \"{}\"\n",
+        manifest_path.replace('\\', "\\\\").replace('"', "\"\"")
+    )
+}
+
+/// Write the manifest and the resource script to `OUT_DIR`, and compile and
+/// link them with `embed-resource`. Does nothing when the target is not Windows.
+#[cfg(feature = "build")]
+pub fn compile(options: &ManifestOptions) -> Result<(), std::io::Error> {
+    use std::{
+        io::{Error, ErrorKind},
+        path::PathBuf,
+    };
+
+    let out_dir = std::env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "OUT_DIR is not set"))?;
+    let manifest_path = out_dir.join("app.manifest");
+    let script_path = out_dir.join("app-manifest.rc");
+    std::fs::write(&manifest_path, manifest(options))?;
+    std::fs::write(
+        &script_path,
+        resource_script(&manifest_path.to_string_lossy()),
+    )?;
+    embed_resource::compile(&script_path, embed_resource::NONE);
+    Ok(())
+}
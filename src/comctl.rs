@@ -0,0 +1,183 @@
+//! The functions of comctl32 version 6, resolved at runtime.
+//!
+//! An application without a visual styles manifest loads comctl32 version 5,
+//! which has none of these functions. Importing them would fail at process
+//! start, so they are looked up when needed instead, optionally with an
+//! activation context which loads version 6.
+
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    fs::OpenOptions,
+    hash::{BuildHasher, Hasher},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    sync::OnceLock,
+    time::SystemTime,
+};
+use widestring::U16CString;
+use windows::{
+    core::{s, w, BOOL, HRESULT, PCSTR, PCWSTR},
+    Win32::{
+        Foundation::{HANDLE, HINSTANCE, HMODULE},
+        System::{
+            ApplicationInstallationAndServicing::{
+                ActivateActCtx, CreateActCtxW, DeactivateActCtx, ACTCTXW,
+            },
+            LibraryLoader::{GetProcAddress, LoadLibraryW},
+        },
+        UI::{Controls::TASKDIALOGCONFIG, WindowsAndMessaging::HICON},
+    },
+};
+
+/// A manifest with the comctl32 version 6 dependency.
+const MANIFEST: &str = include_str!("comctl32-v6.manifest");
+
+/// The activation context of `MANIFEST` and the comctl32 it loads.
+struct ActivationContext {
+    handle: usize,
+    comctl32: Option<usize>,
+}
+
+/// The activation context of `MANIFEST`, created once and kept for the
+/// lifetime of the process. `None` when it could not be created.
+static ACTIVATION_CONTEXT: OnceLock<Option<ActivationContext>> = OnceLock::new();
+
+/// The comctl32 of the manifest of the process, loaded once.
+static COMCTL32: OnceLock<Option<usize>> = OnceLock::new();
+
+thread_local! {
+    /// The number of `ActivationScope`s of the current thread.
+    static ACTIVATED: Cell<usize> = const { Cell::new(0) };
+}
+
+pub(crate) type TaskDialogIndirectFn = unsafe extern "system" fn(
+    config: *const TASKDIALOGCONFIG,
+    button: *mut i32,
+    radio_button: *mut i32,
+    verification_flag_checked: *mut BOOL,
+) -> HRESULT;
+
+pub(crate) type LoadIconWithScaleDownFn = unsafe extern "system" fn(
+    instance: HINSTANCE,
+    name: PCWSTR,
+    cx: i32,
+    cy: i32,
+    icon: *mut HICON,
+) -> HRESULT;
+
+fn load_comctl32() -> Option<usize> {
+    unsafe { LoadLibraryW(w!("comctl32.dll")) }
+        .ok()
+        .map(|module| module.0 as usize)
+}
+
+/// Find a function of comctl32, the version 6 of `MANIFEST` while an
+/// `ActivationScope` is active on the thread, otherwise the version of the
+/// manifest of the process.
+///
+/// The modules are not freed, a dialog may still use them.
+unsafe fn proc_address(name: PCSTR) -> Option<unsafe extern "system" fn() -> isize> {
+    let module = if ACTIVATED.with(|activated| activated.get()) > 0 {
+        ACTIVATION_CONTEXT.get()?.as_ref()?.comctl32?
+    } else {
+        (*COMCTL32.get_or_init(load_comctl32))?
+    };
+    GetProcAddress(HMODULE(module as _), name)
+}
+
+/// `TaskDialogIndirect`, `None` when comctl32 version 6 is not loaded.
+pub(crate) fn task_dialog_indirect() -> Option<TaskDialogIndirectFn> {
+    unsafe { proc_address(s!("TaskDialogIndirect")).map(|f| std::mem::transmute(f)) }
+}
+
+/// `LoadIconWithScaleDown`, `None` when comctl32 version 6 is not loaded.
+pub(crate) fn load_icon_with_scale_down() -> Option<LoadIconWithScaleDownFn> {
+    unsafe { proc_address(s!("LoadIconWithScaleDown")).map(|f| std::mem::transmute(f)) }
+}
+
+/// Create a file for the manifest with a random name, which no other
+/// process can have created or swapped before.
+fn create_manifest_file() -> Option<PathBuf> {
+    for _ in 0..8 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos()),
+        );
+        let path =
+            std::env::temp_dir().join(format!("win-task-dialog-{:016x}.manifest", hasher.finish()));
+        // CREATE_NEW, an existing file is never written or read.
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                if file.write_all(MANIFEST.as_bytes()).is_err() {
+                    drop(file);
+                    std::fs::remove_file(&path).ok();
+                    return None;
+                }
+                return Some(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+fn create_activation_context() -> Option<ActivationContext> {
+    // The manifest is read when the context is created, the file is not needed afterwards.
+    let path = create_manifest_file()?;
+    let source = U16CString::from_os_str_truncate(path.as_os_str());
+    let context = ACTCTXW {
+        cbSize: std::mem::size_of::<ACTCTXW>() as u32,
+        lpSource: PCWSTR(source.as_ptr()),
+        ..Default::default()
+    };
+    let handle = unsafe { CreateActCtxW(&context) };
+    std::fs::remove_file(&path).ok();
+    let handle = handle.ok()?;
+
+    // The comctl32 version 6 of the context, loaded while it is active
+    let mut cookie = 0;
+    let comctl32 = unsafe { ActivateActCtx(Some(handle), &mut cookie) }
+        .ok()
+        .and_then(|()| {
+            let comctl32 = load_comctl32();
+            unsafe { DeactivateActCtx(0, cookie) }.ok();
+            comctl32
+        });
+    Some(ActivationContext {
+        handle: handle.0 as usize,
+        comctl32,
+    })
+}
+
+/// Activates comctl32 version 6 for the current thread until dropped.
+pub(crate) struct ActivationScope {
+    cookie: usize,
+}
+
+impl ActivationScope {
+    /// Activate the context of the embedded manifest, `None` when it could
+    /// not be created or activated.
+    pub(crate) fn activate() -> Option<Self> {
+        let context = ACTIVATION_CONTEXT
+            .get_or_init(create_activation_context)
+            .as_ref()?;
+        let mut cookie = 0;
+        unsafe { ActivateActCtx(Some(HANDLE(context.handle as _)), &mut cookie) }.ok()?;
+        ACTIVATED.with(|activated| activated.set(activated.get() + 1));
+        Some(ActivationScope { cookie })
+    }
+}
+
+impl Drop for ActivationScope {
+    fn drop(&mut self) {
+        ACTIVATED.with(|activated| activated.set(activated.get() - 1));
+        unsafe {
+            DeactivateActCtx(0, self.cookie).ok();
+        }
+    }
+}
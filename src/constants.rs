@@ -1,77 +1,106 @@
 #[cfg(windows)]
 pub use windows::Win32::UI::Controls::{
     TDCBF_CANCEL_BUTTON, TDCBF_CLOSE_BUTTON, TDCBF_NO_BUTTON, TDCBF_OK_BUTTON, TDCBF_RETRY_BUTTON,
-    TDCBF_YES_BUTTON, TDF_ALLOW_DIALOG_CANCELLATION, TDF_CAN_BE_MINIMIZED, TDF_ENABLE_HYPERLINKS,
-    TDF_EXPANDED_BY_DEFAULT, TDF_EXPAND_FOOTER_AREA, TDF_NO_DEFAULT_RADIO_BUTTON,
-    TDF_NO_SET_FOREGROUND, TDF_POSITION_RELATIVE_TO_WINDOW, TDF_RTL_LAYOUT,
-    TDF_SHOW_MARQUEE_PROGRESS_BAR, TDF_SHOW_PROGRESS_BAR, TDF_SIZE_TO_CONTENT,
-    TDF_USE_COMMAND_LINKS, TDF_USE_COMMAND_LINKS_NO_ICON, TDF_VERIFICATION_FLAG_CHECKED,
-    TDM_SET_PROGRESS_BAR_MARQUEE, TDM_SET_PROGRESS_BAR_POS, TDN_BUTTON_CLICKED, TDN_CREATED,
-    TDN_DESTROYED, TDN_HYPERLINK_CLICKED, TDN_NAVIGATED, TD_ERROR_ICON, TD_INFORMATION_ICON,
-    TD_SHIELD_ICON, TD_WARNING_ICON,
+    TDCBF_YES_BUTTON, TDF_ALLOW_DIALOG_CANCELLATION, TDF_CALLBACK_TIMER, TDF_CAN_BE_MINIMIZED,
+    TDF_ENABLE_HYPERLINKS, TDF_EXPANDED_BY_DEFAULT, TDF_EXPAND_FOOTER_AREA,
+    TDF_NO_DEFAULT_RADIO_BUTTON, TDF_NO_SET_FOREGROUND, TDF_POSITION_RELATIVE_TO_WINDOW,
+    TDF_RTL_LAYOUT, TDF_SHOW_MARQUEE_PROGRESS_BAR, TDF_SHOW_PROGRESS_BAR, TDF_SIZE_TO_CONTENT,
+    TDF_USE_COMMAND_LINKS, TDF_USE_COMMAND_LINKS_NO_ICON, TDF_USE_HICON_FOOTER, TDF_USE_HICON_MAIN,
+    TDF_VERIFICATION_FLAG_CHECKED, TDM_SET_PROGRESS_BAR_MARQUEE, TDM_SET_PROGRESS_BAR_POS,
+    TDN_BUTTON_CLICKED, TDN_CREATED, TDN_DESTROYED, TDN_EXPANDO_BUTTON_CLICKED,
+    TDN_HYPERLINK_CLICKED, TDN_NAVIGATED, TDN_RADIO_BUTTON_CLICKED, TDN_TIMER,
+    TDN_VERIFICATION_CLICKED, TD_ERROR_ICON, TD_INFORMATION_ICON, TD_SHIELD_ICON, TD_WARNING_ICON,
 };
 
+// The values of the Win32 headers, so flags can be combined and compared
+// on other platforms too.
 #[cfg(not(windows))]
-pub const TDCBF_CANCEL_BUTTON: u32 = 0;
+pub const TDCBF_CANCEL_BUTTON: u32 = 0x0008;
 #[cfg(not(windows))]
-pub const TDCBF_CLOSE_BUTTON: u32 = 0;
+pub const TDCBF_CLOSE_BUTTON: u32 = 0x0020;
 #[cfg(not(windows))]
-pub const TDCBF_NO_BUTTON: u32 = 0;
+pub const TDCBF_NO_BUTTON: u32 = 0x0004;
 #[cfg(not(windows))]
-pub const TDCBF_OK_BUTTON: u32 = 0;
+pub const TDCBF_OK_BUTTON: u32 = 0x0001;
 #[cfg(not(windows))]
-pub const TDCBF_RETRY_BUTTON: u32 = 0;
+pub const TDCBF_RETRY_BUTTON: u32 = 0x0010;
 #[cfg(not(windows))]
-pub const TDCBF_YES_BUTTON: u32 = 0;
+pub const TDCBF_YES_BUTTON: u32 = 0x0002;
 #[cfg(not(windows))]
-pub const TDF_ALLOW_DIALOG_CANCELLATION: u32 = 0;
+pub const TDF_ALLOW_DIALOG_CANCELLATION: u32 = 0x0008;
 #[cfg(not(windows))]
-pub const TDF_CAN_BE_MINIMIZED: u32 = 0;
+pub const TDF_CALLBACK_TIMER: u32 = 0x0800;
 #[cfg(not(windows))]
-pub const TDF_ENABLE_HYPERLINKS: u32 = 0;
+pub const TDF_CAN_BE_MINIMIZED: u32 = 0x8000;
 #[cfg(not(windows))]
-pub const TDF_EXPANDED_BY_DEFAULT: u32 = 0;
+pub const TDF_ENABLE_HYPERLINKS: u32 = 0x0001;
 #[cfg(not(windows))]
-pub const TDF_EXPAND_FOOTER_AREA: u32 = 0;
+pub const TDF_EXPANDED_BY_DEFAULT: u32 = 0x0080;
 #[cfg(not(windows))]
-pub const TDF_NO_DEFAULT_RADIO_BUTTON: u32 = 0;
+pub const TDF_EXPAND_FOOTER_AREA: u32 = 0x0040;
 #[cfg(not(windows))]
-pub const TDF_NO_SET_FOREGROUND: u32 = 0;
+pub const TDF_NO_DEFAULT_RADIO_BUTTON: u32 = 0x4000;
 #[cfg(not(windows))]
-pub const TDF_POSITION_RELATIVE_TO_WINDOW: u32 = 0;
+pub const TDF_NO_SET_FOREGROUND: u32 = 0x0001_0000;
 #[cfg(not(windows))]
-pub const TDF_RTL_LAYOUT: u32 = 0;
+pub const TDF_POSITION_RELATIVE_TO_WINDOW: u32 = 0x1000;
 #[cfg(not(windows))]
-pub const TDF_SIZE_TO_CONTENT: u32 = 0;
+pub const TDF_RTL_LAYOUT: u32 = 0x2000;
 #[cfg(not(windows))]
-pub const TDF_USE_COMMAND_LINKS: u32 = 0;
+pub const TDF_SIZE_TO_CONTENT: u32 = 0x0100_0000;
 #[cfg(not(windows))]
-pub const TDF_USE_COMMAND_LINKS_NO_ICON: u32 = 0;
+pub const TDF_USE_COMMAND_LINKS: u32 = 0x0010;
 #[cfg(not(windows))]
-pub const TDF_VERIFICATION_FLAG_CHECKED: u32 = 0;
+pub const TDF_USE_COMMAND_LINKS_NO_ICON: u32 = 0x0020;
 #[cfg(not(windows))]
-pub const TD_ERROR_ICON: *mut u16 = std::ptr::null_mut();
+pub const TDF_USE_HICON_FOOTER: u32 = 0x0004;
 #[cfg(not(windows))]
-pub const TD_INFORMATION_ICON: *mut u16 = std::ptr::null_mut();
+pub const TDF_USE_HICON_MAIN: u32 = 0x0002;
 #[cfg(not(windows))]
-pub const TD_SHIELD_ICON: *mut u16 = std::ptr::null_mut();
+pub const TDF_VERIFICATION_FLAG_CHECKED: u32 = 0x0100;
 #[cfg(not(windows))]
-pub const TD_WARNING_ICON: *mut u16 = std::ptr::null_mut();
+pub const TD_ERROR_ICON: crate::PCWSTR = crate::PCWSTR(0xFFFE as *const u16);
 #[cfg(not(windows))]
-pub const TDF_SHOW_MARQUEE_PROGRESS_BAR: u32 = 0;
+pub const TD_INFORMATION_ICON: crate::PCWSTR = crate::PCWSTR(0xFFFD as *const u16);
 #[cfg(not(windows))]
-pub const TDF_SHOW_PROGRESS_BAR: u32 = 0;
+pub const TD_SHIELD_ICON: crate::PCWSTR = crate::PCWSTR(0xFFFC as *const u16);
 #[cfg(not(windows))]
-pub const TDM_SET_PROGRESS_BAR_MARQUEE: u32 = 0;
+pub const TD_WARNING_ICON: crate::PCWSTR = crate::PCWSTR(0xFFFF as *const u16);
 #[cfg(not(windows))]
-pub const TDM_SET_PROGRESS_BAR_POS: u32 = 0;
+pub const TDF_SHOW_MARQUEE_PROGRESS_BAR: u32 = 0x0400;
 #[cfg(not(windows))]
-pub const TDN_BUTTON_CLICKED: u32 = 0;
+pub const TDF_SHOW_PROGRESS_BAR: u32 = 0x0200;
+#[cfg(not(windows))]
+pub const TDM_SET_PROGRESS_BAR_MARQUEE: u32 = 0x046B;
+#[cfg(not(windows))]
+pub const TDM_SET_PROGRESS_BAR_POS: u32 = 0x046A;
+#[cfg(not(windows))]
+pub const TDN_BUTTON_CLICKED: u32 = 2;
 #[cfg(not(windows))]
 pub const TDN_CREATED: u32 = 0;
 #[cfg(not(windows))]
-pub const TDN_DESTROYED: u32 = 0;
+pub const TDN_DESTROYED: u32 = 5;
+#[cfg(not(windows))]
+pub const TDN_EXPANDO_BUTTON_CLICKED: u32 = 10;
 #[cfg(not(windows))]
-pub const TDN_HYPERLINK_CLICKED: u32 = 0;
+pub const TDN_HYPERLINK_CLICKED: u32 = 3;
 #[cfg(not(windows))]
-pub const TDN_NAVIGATED: u32 = 0;
+pub const TDN_NAVIGATED: u32 = 1;
+#[cfg(not(windows))]
+pub const TDN_RADIO_BUTTON_CLICKED: u32 = 6;
+#[cfg(not(windows))]
+pub const TDN_TIMER: u32 = 4;
+#[cfg(not(windows))]
+pub const TDN_VERIFICATION_CLICKED: u32 = 8;
+
+// The button IDs, as returned in `TaskDialogResult::button_id` and by `message_box`.
+pub const IDOK: i32 = 1;
+pub const IDCANCEL: i32 = 2;
+pub const IDABORT: i32 = 3;
+pub const IDRETRY: i32 = 4;
+pub const IDIGNORE: i32 = 5;
+pub const IDYES: i32 = 6;
+pub const IDNO: i32 = 7;
+pub const IDCLOSE: i32 = 8;
+pub const IDTRYAGAIN: i32 = 10;
+pub const IDCONTINUE: i32 = 11;
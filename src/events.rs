@@ -0,0 +1,46 @@
+use crate::*;
+use std::sync::mpsc::{channel, Receiver};
+
+/// A notification of the dialog, see `TaskDialogConfig::events`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskDialogEvent {
+    Created,
+    /// The dialog navigated to this page.
+    Navigated,
+    ButtonClicked {
+        button_id: i32,
+        /// The dialog stays open, the callback returned `S_FALSE` or the
+        /// action of the button kept the dialog open.
+        vetoed: bool,
+    },
+    RadioButtonClicked(i32),
+    /// The verification checkbox is checked.
+    VerificationClicked(bool),
+    HyperlinkClicked(String),
+    /// The expandable area is expanded.
+    ExpanderToggled(bool),
+    /// The milliseconds since the dialog was created or the timer was
+    /// reset, sent about every 200 milliseconds with `TDF_CALLBACK_TIMER`.
+    Timer(u32),
+    Destroyed,
+}
+
+impl TaskDialogConfig {
+    /** Receive the notifications of the dialog on another thread or task.
+
+    The pages the dialog navigates to send to the same receiver, unless
+    they have their own. The receiver is disconnected when the config and
+    its pages are dropped. */
+    pub fn events(&mut self) -> Receiver<TaskDialogEvent> {
+        let (sender, receiver) = channel();
+        self.event_sender = Some(sender);
+        receiver
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn emit(&self, event: TaskDialogEvent) {
+        if let Some(sender) = &self.event_sender {
+            sender.send(event).ok();
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::*;
+use std::{
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default)]
+struct Shared {
+    result: Option<Result<TaskDialogResult, TaskDialogError>>,
+    waker: Option<Waker>,
+}
+
+/// The future of `show_task_dialog_async`.
+///
+/// Dropping the future before the dialog is closed closes the dialog with `IDCANCEL`.
+pub struct TaskDialogFuture {
+    handle: TaskDialogHandle,
+    shared: Arc<Mutex<Shared>>,
+    done: bool,
+}
+
+impl TaskDialogFuture {
+    /// The handle to update the dialog while the future is pending.
+    pub fn handle(&self) -> &TaskDialogHandle {
+        &self.handle
+    }
+}
+
+impl Future for TaskDialogFuture {
+    type Output = Result<TaskDialogResult, TaskDialogError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = {
+            let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+            if shared.result.is_none() {
+                shared.waker = Some(cx.waker().clone());
+            }
+            shared.result.take()
+        };
+        match result {
+            Some(result) => {
+                self.done = true;
+                Poll::Ready(result)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for TaskDialogFuture {
+    fn drop(&mut self) {
+        if !self.done {
+            self.handle.close(IDCANCEL);
+        }
+    }
+}
+
+/** Show the dialog on a new thread and complete when it is closed, the
+future works with any async runtime.
+
+The dialog is shown when this function is called, not when the future is
+first polled. */
+pub fn show_task_dialog_async(mut conf: TaskDialogConfig) -> TaskDialogFuture {
+    let handle = conf
+        .handle
+        .get_or_insert_with(TaskDialogHandle::new)
+        .clone();
+    handle.begin();
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    let thread_shared = shared.clone();
+    std::thread::Builder::new()
+        .name("task-dialog".to_string())
+        .spawn(move || {
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| show_task_dialog(&mut conf)))
+                .unwrap_or_else(|_| Err(TaskDialogError::other("the dialog thread panicked")));
+            let waker = {
+                let mut shared = thread_shared.lock().unwrap_or_else(|e| e.into_inner());
+                shared.result = Some(result);
+                shared.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        })
+        .expect("failed to spawn the dialog thread");
+    TaskDialogFuture {
+        handle,
+        shared,
+        done: false,
+    }
+}
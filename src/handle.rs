@@ -0,0 +1,368 @@
+use crate::*;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(windows)]
+use windows::Win32::UI::{
+    Controls::{
+        TASKDIALOG_MESSAGES, TDE_CONTENT, TDE_EXPANDED_INFORMATION, TDE_FOOTER,
+        TDE_MAIN_INSTRUCTION, TDM_CLICK_BUTTON, TDM_ENABLE_BUTTON, TDM_ENABLE_RADIO_BUTTON,
+        TDM_SET_BUTTON_ELEVATION_REQUIRED_STATE, TDM_SET_MARQUEE_PROGRESS_BAR,
+        TDM_UPDATE_ELEMENT_TEXT,
+    },
+    WindowsAndMessaging::{PostMessageW, SendMessageA, WM_CLOSE},
+};
+
+#[derive(Default)]
+struct HandleState {
+    /// The `HWND` of the open dialog, 0 when the dialog is not open.
+    hwnd: isize,
+    /// The outcome of `close`, reported as the clicked button.
+    close_outcome: Option<i32>,
+    /// The IDs and the enabled state of the buttons of the open dialog.
+    buttons: Vec<(i32, bool)>,
+    /// The dialog closes on `WM_CLOSE`, it has `TDF_ALLOW_DIALOG_CANCELLATION`
+    /// or a Cancel button.
+    cancellable: bool,
+    /// The dialog was destroyed or returned, `close` is ignored until it is
+    /// shown again.
+    finished: bool,
+}
+
+/// How the dialog is closed, see `HandleState::close_message`.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Clone, Copy)]
+enum CloseMessage {
+    Click(i32),
+    Close,
+}
+
+impl HandleState {
+    /** How to close the dialog with `outcome`, which replaces the result:
+    click `outcome` when it is an enabled button, otherwise an enabled
+    Cancel or Close button, otherwise send `WM_CLOSE` when the dialog can
+    be cancelled, otherwise click any enabled button. The callback of the
+    config is not called for the click. */
+    fn close_message(&self, outcome: i32) -> CloseMessage {
+        let enabled = |id: i32| self.buttons.contains(&(id, true));
+        if enabled(outcome) {
+            CloseMessage::Click(outcome)
+        } else if let Some(id) = [IDCANCEL, IDCLOSE].iter().copied().find(|&id| enabled(id)) {
+            CloseMessage::Click(id)
+        } else if self.cancellable {
+            CloseMessage::Close
+        } else {
+            match self.buttons.iter().find(|(_, enabled)| *enabled) {
+                Some(&(id, _)) => CloseMessage::Click(id),
+                None => CloseMessage::Close,
+            }
+        }
+    }
+}
+
+/// A handle to update and close a dialog from other threads.
+///
+/// Set it as `TaskDialogConfig::handle` before the dialog is shown, it is
+/// attached when the dialog is created. The handle only sends messages to
+/// the dialog, the text fields of the config are not updated.
+#[derive(Clone, Default)]
+pub struct TaskDialogHandle {
+    state: Arc<Mutex<HandleState>>,
+}
+
+impl TaskDialogHandle {
+    pub fn new() -> Self {
+        TaskDialogHandle::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, HandleState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The dialog is created and not yet closed.
+    pub fn is_open(&self) -> bool {
+        self.state().hwnd != 0
+    }
+
+    /// The `HWND` of the open dialog.
+    pub fn hwnd(&self) -> Option<HWND> {
+        match self.state().hwnd {
+            0 => None,
+            hwnd => Some(HWND(hwnd as _)),
+        }
+    }
+
+    /// Close the dialog and report `outcome` as the clicked button, e.g.
+    /// `IDCANCEL`. The outcome does not need to be a button of the dialog.
+    /// The callback of the config is not called for the button clicked to
+    /// close the dialog, and cannot keep it open. When the dialog is not
+    /// created yet, it is closed as soon as it is created. Does nothing
+    /// once the dialog returned.
+    pub fn close(&self, outcome: i32) {
+        let (hwnd, message) = {
+            let mut state = self.state();
+            if state.finished {
+                return;
+            }
+            state.close_outcome = Some(outcome);
+            (state.hwnd, state.close_message(outcome))
+        };
+        if hwnd != 0 {
+            post_close(HWND(hwnd as _), message);
+        }
+    }
+
+    /// Both handles refer to the same dialog.
+    pub fn same_dialog(&self, other: &TaskDialogHandle) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl TaskDialogHandle {
+    /// Called when the dialog is created or navigated to the page `conf`.
+    pub(crate) fn attach(&self, hwnd: HWND, conf: &TaskDialogConfig) {
+        let message = {
+            let mut state = self.state();
+            state.hwnd = hwnd.0 as _;
+            state.buttons = conf.dialog_buttons();
+            state.cancellable = conf.flags & TDF_ALLOW_DIALOG_CANCELLATION
+                == TDF_ALLOW_DIALOG_CANCELLATION
+                || state.buttons.iter().any(|&(id, _)| id == IDCANCEL);
+            state
+                .close_outcome
+                .map(|outcome| state.close_message(outcome))
+        };
+        if let Some(message) = message {
+            post_close(hwnd, message);
+        }
+    }
+
+    /// Called when the dialog is destroyed.
+    pub(crate) fn detach(&self) {
+        let mut state = self.state();
+        state.hwnd = 0;
+        state.buttons.clear();
+        state.finished = true;
+    }
+
+    /// The dialog was destroyed or returned, and is not shown again yet.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.state().finished
+    }
+
+    /// Called when a button of the open dialog is enabled or disabled.
+    pub(crate) fn button_enabled(&self, button_id: i32, enable: bool) {
+        let mut state = self.state();
+        if let Some(button) = state.buttons.iter_mut().find(|(id, _)| *id == button_id) {
+            button.1 = enable;
+        }
+    }
+
+    /// The outcome of a requested `close`.
+    pub(crate) fn close_outcome(&self) -> Option<i32> {
+        self.state().close_outcome
+    }
+
+    /// Called before the dialog is shown, `close` closes it again.
+    pub(crate) fn begin(&self) {
+        self.state().finished = false;
+    }
+
+    /// Called when the dialog returned, take the outcome of `close`. The
+    /// handle ignores `close` until the dialog is shown again.
+    pub(crate) fn finish(&self) -> Option<i32> {
+        let mut state = self.state();
+        state.finished = true;
+        state.close_outcome.take()
+    }
+}
+
+#[cfg(windows)]
+fn post_close(hwnd: HWND, message: CloseMessage) {
+    let (msg, w_param) = match message {
+        CloseMessage::Click(button) => (TDM_CLICK_BUTTON.0 as u32, button as usize),
+        CloseMessage::Close => (WM_CLOSE, 0),
+    };
+    unsafe {
+        PostMessageW(Some(hwnd), msg, WPARAM(w_param), LPARAM(0)).ok();
+    }
+}
+
+#[cfg(not(windows))]
+fn post_close(_hwnd: HWND, _message: CloseMessage) {}
+
+impl TaskDialogConfig {
+    /** Close the open dialog and report `outcome` as the clicked button,
+    see `TaskDialogHandle::close`. Use `handle` or `DialogRegistry` to
+    close it from other threads.
+
+    Does nothing when the dialog is not shown and has no handle. */
+    pub fn close(&mut self, outcome: i32) {
+        if let Some(handle) = &self.handle {
+            handle.close(outcome);
+        }
+    }
+
+    /// The IDs and the enabled state of the buttons the dialog shows, the
+    /// dialog shows an OK button when it has none.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn dialog_buttons(&self) -> Vec<(i32, bool)> {
+        let mut ids: Vec<(i32, bool)> = self
+            .buttons
+            .iter()
+            .map(|btn| (btn.id, btn.enabled))
+            .collect();
+        for (flag, id) in [
+            (TDCBF_OK_BUTTON, IDOK),
+            (TDCBF_YES_BUTTON, IDYES),
+            (TDCBF_NO_BUTTON, IDNO),
+            (TDCBF_CANCEL_BUTTON, IDCANCEL),
+            (TDCBF_RETRY_BUTTON, IDRETRY),
+            (TDCBF_CLOSE_BUTTON, IDCLOSE),
+        ] {
+            if self.common_buttons & flag == flag {
+                ids.push((id, true));
+            }
+        }
+        if ids.is_empty() {
+            ids.push((IDOK, true));
+        }
+        ids
+    }
+}
+
+#[cfg(windows)]
+impl TaskDialogHandle {
+    /// Send a message to the open dialog and wait until it is processed.
+    fn send(&self, msg: TASKDIALOG_MESSAGES, w_param: usize, l_param: isize) {
+        // The lock is not held while sending, the dialog thread needs it
+        // to handle the notifications.
+        if let Some(hwnd) = self.hwnd() {
+            unsafe {
+                SendMessageA(hwnd, msg.0 as _, WPARAM(w_param), LPARAM(l_param));
+            }
+        }
+    }
+
+    fn send_text(&self, element: i32, text: &str) {
+        let text = U16CString::from_str_truncate(text);
+        self.send(TDM_UPDATE_ELEMENT_TEXT, element as _, text.as_ptr() as _);
+    }
+
+    /// Set the content text
+    pub fn set_content(&self, content: &str) {
+        self.send_text(TDE_CONTENT.0, content);
+    }
+
+    /// Set the main instruction text
+    pub fn set_main_instruction(&self, main_instruction: &str) {
+        self.send_text(TDE_MAIN_INSTRUCTION.0, main_instruction);
+    }
+
+    /// Set the footer text
+    pub fn set_footer(&self, footer: &str) {
+        self.send_text(TDE_FOOTER.0, footer);
+    }
+
+    /// Set the expanded information text
+    pub fn set_expanded_information(&self, expanded_information: &str) {
+        self.send_text(TDE_EXPANDED_INFORMATION.0, expanded_information);
+    }
+
+    /// Set the percentage of the progress bar
+    pub fn set_process_bar(&self, percentage: usize) {
+        self.send(TDM_SET_PROGRESS_BAR_POS, percentage, 0);
+    }
+
+    /// Set status or animation time of marquee progress bar
+    pub fn set_process_bar_marquee(&self, enable: bool, time: isize) {
+        self.send(TDM_SET_PROGRESS_BAR_MARQUEE, enable as _, time);
+    }
+
+    /// Switch the progress bar between marquee and normal mode, the dialog
+    /// needs one of the progress bar flags
+    pub fn set_marquee_process_bar(&self, marquee: bool) {
+        self.send(TDM_SET_MARQUEE_PROGRESS_BAR, marquee as _, 0);
+    }
+
+    /// Enable or disable the button
+    pub fn set_button_enabled(&self, button_id: usize, enable: bool) {
+        self.button_enabled(button_id as _, enable);
+        self.send(TDM_ENABLE_BUTTON, button_id, enable as _);
+    }
+
+    /// Enable or disable the radio button
+    pub fn set_radio_button_enabled(&self, button_id: usize, enable: bool) {
+        self.send(TDM_ENABLE_RADIO_BUTTON, button_id, enable as _);
+    }
+
+    /// Set the button elevation state
+    pub fn set_button_elevation_required_state(&self, button_id: usize, enable: bool) {
+        self.send(
+            TDM_SET_BUTTON_ELEVATION_REQUIRED_STATE,
+            button_id,
+            enable as _,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+impl TaskDialogHandle {
+    pub fn set_content(&self, _content: &str) {}
+    pub fn set_main_instruction(&self, _main_instruction: &str) {}
+    pub fn set_footer(&self, _footer: &str) {}
+    pub fn set_expanded_information(&self, _expanded_information: &str) {}
+    pub fn set_process_bar(&self, _percentage: usize) {}
+    pub fn set_process_bar_marquee(&self, _enable: bool, _time: isize) {}
+    pub fn set_marquee_process_bar(&self, _marquee: bool) {}
+    pub fn set_button_enabled(&self, _button_id: usize, _enable: bool) {}
+    pub fn set_radio_button_enabled(&self, _button_id: usize, _enable: bool) {}
+    pub fn set_button_elevation_required_state(&self, _button_id: usize, _enable: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_is_ignored_once_the_dialog_returned() {
+        let handle = TaskDialogHandle::new();
+        // Closed as soon as it is created
+        handle.close(IDCANCEL);
+        assert_eq!(handle.close_outcome(), Some(IDCANCEL));
+        assert_eq!(handle.finish(), Some(IDCANCEL));
+
+        handle.close(IDOK);
+        assert_eq!(handle.close_outcome(), None);
+
+        // Shown again
+        handle.begin();
+        assert_eq!(handle.close_outcome(), None);
+        handle.close(IDOK);
+        assert_eq!(handle.finish(), Some(IDOK));
+    }
+
+    #[test]
+    fn close_prefers_enabled_buttons() {
+        let mut state = HandleState {
+            buttons: vec![(100, false), (IDCANCEL, true), (IDOK, true)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            state.close_message(IDOK),
+            CloseMessage::Click(IDOK)
+        ));
+        assert!(matches!(
+            state.close_message(100),
+            CloseMessage::Click(IDCANCEL)
+        ));
+
+        state.buttons = vec![(100, false), (IDOK, true)];
+        assert!(matches!(
+            state.close_message(100),
+            CloseMessage::Click(IDOK)
+        ));
+        state.cancellable = true;
+        assert!(matches!(state.close_message(100), CloseMessage::Close));
+    }
+}
@@ -0,0 +1,360 @@
+#[cfg(windows)]
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::HMODULE,
+        UI::{
+            Controls::{TD_ERROR_ICON, TD_INFORMATION_ICON, TD_SHIELD_ICON, TD_WARNING_ICON},
+            WindowsAndMessaging::{
+                CreateIconFromResourceEx, DestroyIcon, GetSystemMetrics, HICON, IDI_ERROR,
+                IDI_INFORMATION, IDI_SHIELD, IDI_WARNING, LR_DEFAULTCOLOR, SM_CXICON, SM_CXSMICON,
+            },
+        },
+    },
+};
+
+#[cfg(not(windows))]
+use crate::{HMODULE, PCWSTR};
+
+use std::{
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// One image of an `.ico` file.
+pub struct IcoImage<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub bit_count: u16,
+    /// The image is stored as PNG instead of a DIB.
+    pub is_png: bool,
+    /// The image data, as expected by `CreateIconFromResourceEx`.
+    pub data: &'a [u8],
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn invalid_ico(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("invalid icon: {}", msg))
+}
+
+/** Parse the images of an `.ico` file, both DIB and PNG images are supported.
+
+```
+use win_task_dialog::parse_ico;
+
+// An `.ico` file with the images of the (width, height, bit count, data) entries.
+fn ico(entries: &[(u8, u8, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut bytes = vec![0, 0, 1, 0];
+    bytes.extend((entries.len() as u16).to_le_bytes());
+    let mut offset = 6 + entries.len() * 16;
+    for (width, height, bit_count, data) in entries {
+        bytes.extend([*width, *height, 0, 0, 1, 0]);
+        bytes.extend(bit_count.to_le_bytes());
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend((offset as u32).to_le_bytes());
+        offset += data.len();
+    }
+    for (.., data) in entries {
+        bytes.extend(data);
+    }
+    bytes
+}
+
+// A BITMAPINFOHEADER of 8 bits per pixel.
+let mut dib = vec![0; 40];
+dib[14] = 8;
+// The signature and the IHDR chunk of a 48x48 PNG image.
+let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+png.extend([0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+png.extend(48u32.to_be_bytes());
+png.extend(48u32.to_be_bytes());
+png.extend([8, 6]);
+
+// A size of 0 is 256 pixels, the bit count of 0 is read from the bitmap.
+// The size of a PNG image is read from the IHDR chunk.
+let bytes = ico(&[(0, 0, 0, dib.clone()), (16, 16, 32, png.clone())]);
+let images = parse_ico(&bytes).unwrap();
+assert_eq!(images.len(), 2);
+let image = &images[0];
+assert_eq!((image.width, image.height, image.bit_count, image.is_png), (256, 256, 8, false));
+assert_eq!(image.data, &dib[..]);
+let image = &images[1];
+assert_eq!((image.width, image.height, image.bit_count, image.is_png), (48, 48, 32, true));
+assert_eq!(image.data, &png[..]);
+
+// Truncated or out of bounds data.
+assert!(parse_ico(&[0, 0, 1, 0, 0, 0]).is_err());
+assert!(parse_ico(&bytes[..20]).is_err());
+assert!(parse_ico(&bytes[..bytes.len() - 1]).is_err());
+assert!(parse_ico(&ico(&[(16, 16, 32, vec![0; 39])])).is_err());
+assert!(parse_ico(&ico(&[(16, 16, 32, png[..25].to_vec())])).is_err());
+```
+*/
+pub fn parse_ico(bytes: &[u8]) -> Result<Vec<IcoImage<'_>>, Error> {
+    if bytes.len() < 6 || read_u16(bytes, 0) != 0 || read_u16(bytes, 2) != 1 {
+        return Err(invalid_ico("missing ICONDIR header"));
+    }
+    let count = read_u16(bytes, 4) as usize;
+    if count == 0 {
+        return Err(invalid_ico("no images"));
+    }
+    if bytes.len() < 6 + count * 16 {
+        return Err(invalid_ico("truncated directory"));
+    }
+
+    let mut images = Vec::with_capacity(count);
+    for i in 0..count {
+        let entry = 6 + i * 16;
+        let size = read_u32(bytes, entry + 8) as usize;
+        let offset = read_u32(bytes, entry + 12) as usize;
+        let data = match offset.checked_add(size) {
+            Some(end) if end <= bytes.len() => &bytes[offset..end],
+            _ => return Err(invalid_ico("image data out of bounds")),
+        };
+
+        // A width or height of 0 means 256 pixels.
+        let mut width = if bytes[entry] == 0 {
+            256
+        } else {
+            bytes[entry] as u32
+        };
+        let mut height = if bytes[entry + 1] == 0 {
+            256
+        } else {
+            bytes[entry + 1] as u32
+        };
+        let mut bit_count = read_u16(bytes, entry + 6);
+        let is_png = data.starts_with(&PNG_SIGNATURE);
+        if is_png {
+            // The directory entry of a PNG image is not reliable, read the IHDR chunk.
+            if data.len() < 26 {
+                return Err(invalid_ico("truncated PNG image"));
+            }
+            width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+            height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+            if bit_count == 0 {
+                bit_count = 32;
+            }
+        } else if data.len() < 40 {
+            return Err(invalid_ico("truncated bitmap image"));
+        } else if bit_count == 0 {
+            bit_count = read_u16(data, 14);
+        }
+
+        images.push(IcoImage {
+            width,
+            height,
+            bit_count,
+            is_png,
+            data,
+        });
+    }
+    Ok(images)
+}
+
+/** Pick the image to show at `size` pixels: an exact match, otherwise the
+smallest larger image, otherwise the largest smaller image. Ties are
+broken by the higher color depth.
+
+```
+use win_task_dialog::{select_ico_image, IcoImage};
+
+let image = |size: u32, bit_count: u16| IcoImage {
+    width: size,
+    height: size,
+    bit_count,
+    is_png: false,
+    data: &[],
+};
+let images = [image(16, 32), image(32, 8), image(32, 32), image(48, 4)];
+let select = |size| select_ico_image(&images, size).map(|i| (i.width, i.bit_count));
+assert_eq!(select(32), Some((32, 32)));
+assert_eq!(select(24), Some((32, 32)));
+assert_eq!(select(40), Some((48, 4)));
+assert_eq!(select(128), Some((48, 4)));
+assert!(select_ico_image(&[], 32).is_none());
+```
+*/
+pub fn select_ico_image<'a, 'b>(images: &'b [IcoImage<'a>], size: u32) -> Option<&'b IcoImage<'a>> {
+    images.iter().min_by_key(|image| {
+        let dimension = image.width.max(image.height);
+        let distance = if dimension >= size {
+            (0, dimension - size)
+        } else {
+            (1, size - dimension)
+        };
+        (distance, std::cmp::Reverse(image.bit_count))
+    })
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+enum IconSource {
+    Stock(PCWSTR),
+    Ico(Vec<u8>),
+    Resource(HMODULE, u16),
+}
+
+/// A custom icon for the main or footer icon of the dialog.
+///
+/// A stock icon such as `TD_WARNING_ICON` converts into a `DialogIcon`, so
+/// a dialog created with a custom icon can switch back to a stock icon.
+///
+/// The `HICON` is created when the dialog is shown, and destroyed when
+/// the `DialogIcon` is dropped.
+pub struct DialogIcon {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    source: IconSource,
+    #[cfg(windows)]
+    handle: Option<(u32, HICON)>,
+}
+
+impl DialogIcon {
+    fn new(source: IconSource) -> Self {
+        DialogIcon {
+            source,
+            #[cfg(windows)]
+            handle: None,
+        }
+    }
+
+    /// Use a stock icon, e.g. `TD_WARNING_ICON`.
+    pub fn from_stock(icon: PCWSTR) -> Self {
+        DialogIcon::new(IconSource::Stock(icon))
+    }
+
+    /// The stock icon, if this is not a custom icon.
+    pub fn stock(&self) -> Option<PCWSTR> {
+        match self.source {
+            IconSource::Stock(icon) => Some(icon),
+            _ => None,
+        }
+    }
+
+    /// Use the contents of an `.ico` file, e.g. from `include_bytes!`.
+    pub fn from_ico_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        parse_ico(bytes)?;
+        Ok(DialogIcon::new(IconSource::Ico(bytes.to_vec())))
+    }
+
+    /// Read an `.ico` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        DialogIcon::from_ico_bytes(&std::fs::read(path)?)
+    }
+
+    /// Use an icon resource of `module`, pass the default `HMODULE` for
+    /// the current process.
+    pub fn from_resource(module: HMODULE, id: u16) -> Self {
+        DialogIcon::new(IconSource::Resource(module, id))
+    }
+}
+
+impl From<PCWSTR> for DialogIcon {
+    fn from(icon: PCWSTR) -> Self {
+        DialogIcon::from_stock(icon)
+    }
+}
+
+#[cfg(windows)]
+impl DialogIcon {
+    /// Get the `HICON` for the main icon size of the current DPI.
+    pub(crate) fn main_handle(&mut self) -> Result<HICON, Error> {
+        self.handle(unsafe { GetSystemMetrics(SM_CXICON) } as u32)
+    }
+
+    /// Get the `HICON` for the footer icon size of the current DPI.
+    pub(crate) fn footer_handle(&mut self) -> Result<HICON, Error> {
+        self.handle(unsafe { GetSystemMetrics(SM_CXSMICON) } as u32)
+    }
+
+    fn handle(&mut self, size: u32) -> Result<HICON, Error> {
+        if let Some((handle_size, handle)) = self.handle {
+            if handle_size == size {
+                return Ok(handle);
+            }
+        }
+        let handle = unsafe {
+            match &self.source {
+                IconSource::Stock(icon) => {
+                    // The stock icons of the dialog are only valid as a resource
+                    // of comctl32, load the matching system icons instead.
+                    let name = match *icon {
+                        TD_WARNING_ICON => IDI_WARNING,
+                        TD_ERROR_ICON => IDI_ERROR,
+                        TD_INFORMATION_ICON => IDI_INFORMATION,
+                        TD_SHIELD_ICON => IDI_SHIELD,
+                        icon => icon,
+                    };
+                    load_icon_with_scale_down(HMODULE::default(), name, size)
+                }
+                IconSource::Ico(bytes) => {
+                    let images = parse_ico(bytes)?;
+                    let image =
+                        select_ico_image(&images, size).ok_or_else(|| invalid_ico("no images"))?;
+                    CreateIconFromResourceEx(
+                        image.data,
+                        true,
+                        0x00030000,
+                        size as i32,
+                        size as i32,
+                        LR_DEFAULTCOLOR,
+                    )
+                }
+                IconSource::Resource(module, id) => {
+                    let module = if module.is_invalid() {
+                        use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+
+                        GetModuleHandleA(None).unwrap()
+                    } else {
+                        *module
+                    };
+                    load_icon_with_scale_down(module, PCWSTR(*id as usize as *const u16), size)
+                }
+            }
+        }
+        .map_err(Error::from)?;
+        self.destroy();
+        self.handle = Some((size, handle));
+        Ok(handle)
+    }
+
+    fn destroy(&mut self) {
+        if let Some((_, handle)) = self.handle.take() {
+            unsafe {
+                DestroyIcon(handle).ok();
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe fn load_icon_with_scale_down(
+    module: HMODULE,
+    name: PCWSTR,
+    size: u32,
+) -> windows::core::Result<HICON> {
+    let load = crate::comctl::load_icon_with_scale_down()
+        .ok_or_else(|| windows::core::Error::from(windows::Win32::Foundation::E_NOTIMPL))?;
+    let mut icon = HICON::default();
+    load(module.into(), name, size as i32, size as i32, &mut icon).map(|| icon)
+}
+
+#[cfg(windows)]
+impl Drop for DialogIcon {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
@@ -15,10 +15,11 @@ use windows::{
         Foundation::{FALSE, HMODULE},
         UI::{
             Controls::{
-                TaskDialogIndirect, TASKDIALOGCONFIG, TASKDIALOGCONFIG_0, TASKDIALOGCONFIG_1,
-                TASKDIALOG_BUTTON, TASKDIALOG_COMMON_BUTTON_FLAGS, TASKDIALOG_FLAGS, TDE_CONTENT,
-                TDE_EXPANDED_INFORMATION, TDM_NAVIGATE_PAGE,
-                TDM_SET_BUTTON_ELEVATION_REQUIRED_STATE, TDM_UPDATE_ELEMENT_TEXT,
+                TASKDIALOGCONFIG, TASKDIALOGCONFIG_0, TASKDIALOGCONFIG_1, TASKDIALOG_BUTTON,
+                TASKDIALOG_COMMON_BUTTON_FLAGS, TASKDIALOG_FLAGS, TASKDIALOG_ICON_ELEMENTS,
+                TDE_CONTENT, TDE_EXPANDED_INFORMATION, TDIE_ICON_FOOTER, TDIE_ICON_MAIN,
+                TDM_ENABLE_BUTTON, TDM_ENABLE_RADIO_BUTTON, TDM_NAVIGATE_PAGE,
+                TDM_SET_BUTTON_ELEVATION_REQUIRED_STATE, TDM_UPDATE_ELEMENT_TEXT, TDM_UPDATE_ICON,
             },
             WindowsAndMessaging::SendMessageA,
         },
@@ -26,34 +27,17 @@ use windows::{
 };
 
 #[cfg(not(windows))]
-pub type HWND = *mut usize;
-
-#[cfg(not(windows))]
-type HMODULE = *mut usize;
-
-#[cfg(not(windows))]
-type PWSTR = *mut u16;
-
-#[cfg(not(windows))]
-type WPARAM = usize;
-
+mod stubs;
 #[cfg(not(windows))]
-type LPARAM = isize;
+pub use stubs::*;
 
-#[cfg(not(windows))]
-type HRESULT = i32;
-
-type UINT = u32;
-
-#[cfg(not(windows))]
-#[allow(non_camel_case_types)]
-type TASKDIALOG_FLAGS = u32;
-
-#[cfg(not(windows))]
-#[allow(non_camel_case_types)]
-type TASKDIALOG_COMMON_BUTTON_FLAGS = u32;
+use std::io::Error;
+#[cfg(windows)]
+use std::io::ErrorKind;
+use std::sync::{mpsc::Sender, Arc};
 
-use std::{io::Error, usize};
+/// The error of the dialog functions.
+pub type TaskDialogError = std::io::Error;
 
 pub type TaskDialogHyperlinkCallback = Option<fn(context: &str) -> ()>;
 
@@ -75,6 +59,71 @@ pub enum ExecuteOption {
 mod constants;
 pub use constants::*;
 
+mod icon;
+pub use icon::*;
+
+mod presets;
+pub use presets::*;
+
+mod shell;
+pub use shell::open_hyperlink;
+
+mod panic;
+pub use panic::*;
+
+mod result_ext;
+pub use result_ext::*;
+
+mod backend;
+pub use backend::*;
+
+mod retry;
+pub use retry::*;
+
+mod message_box;
+pub use message_box::*;
+
+mod width;
+pub use width::*;
+
+#[cfg(feature = "raw-window-handle")]
+mod window_handle;
+
+mod handle;
+pub use handle::*;
+
+mod spawn;
+pub use spawn::*;
+
+mod future;
+pub use future::*;
+
+mod events;
+pub use events::*;
+
+mod registry;
+pub use registry::*;
+
+mod queue;
+pub use queue::*;
+
+mod suppress;
+pub use suppress::*;
+
+mod progress;
+pub use progress::*;
+
+mod progress_format;
+pub use progress_format::*;
+
+mod staged;
+pub use staged::*;
+
+#[cfg(windows)]
+mod comctl;
+
+pub mod build;
+
 pub struct TaskDialogConfig {
     pub parent: HWND,
     pub instance: HMODULE,
@@ -88,21 +137,55 @@ pub struct TaskDialogConfig {
     pub expanded_control_text: String,
     pub collapsed_control_text: String,
     pub footer: String,
+    /** The expandable area, when set it replaces `expanded_information`,
+    `expanded_control_text`, `collapsed_control_text` and the
+    `TDF_EXPANDED_BY_DEFAULT` and `TDF_EXPAND_FOOTER_AREA` flags. */
+    pub expander: Option<Expander>,
     pub buttons: Vec<TaskDialogButton>,
     pub default_button: i32,
     pub radio_buttons: Vec<TaskDialogButton>,
     pub default_radio_buttons: i32,
     pub main_icon: PCWSTR,
     pub footer_icon: PCWSTR,
+    /** Show a custom icon instead of `main_icon`. */
+    pub custom_main_icon: Option<DialogIcon>,
+    /** Show a custom icon instead of `footer_icon`. */
+    pub custom_footer_icon: Option<DialogIcon>,
     /** When created dialog, the value set to HWND. */
     pub dialog_hwnd: HWND,
-    /** When close the dialog, the value set to true, default is false. */
+    /** When close the dialog, the value set to true, default is false.
+    Also set on the config shown first when the dialog closes on a page it
+    navigated to. */
     pub is_destroyed: bool,
+    /** When the user clicks the expando button, the value set to the expanded
+    state. The config shown first follows the page the dialog shows. */
+    pub is_expanded: bool,
     pub hyperlink_callback: TaskDialogHyperlinkCallback,
     pub callback: TaskDialogWndProcCallback,
+    /** The width of the dialog in horizontal dialog units, 0 for the width
+    chosen by the dialog. Ignored when `width` is not `DialogWidth::Auto`. */
     pub cx_width: u32,
+    /** The width of the dialog in pixels, characters or dialog units. */
+    pub width: DialogWidth,
+    /** Activate comctl32 version 6 while the dialog is shown, for libraries
+    and plugins which cannot add a visual styles manifest to the executable. */
+    pub activation_context: bool,
+    /** Show the dialog with `MessageBoxW` when comctl32 version 6 is not
+    loaded, default is `true`. Otherwise the dialog returns an `Unsupported`
+    error. */
+    pub message_box_fallback: bool,
+    /** Update and close the dialog from other threads, shared with the
+    pages the dialog navigates to. When `None`, a handle is set while the
+    dialog is shown, for `close` and `DialogRegistry`. */
+    pub handle: Option<TaskDialogHandle>,
+    /** Send the notifications of the dialog, see `events`. */
+    pub event_sender: Option<Sender<TaskDialogEvent>>,
 }
 
+// The handles of the config are only passed to the dialog, which may be
+// shown on another thread, see `spawn_task_dialog`.
+unsafe impl Send for TaskDialogConfig {}
+
 impl Default for TaskDialogConfig {
     fn default() -> Self {
         TaskDialogConfig {
@@ -118,17 +201,26 @@ impl Default for TaskDialogConfig {
             expanded_control_text: "".to_string(),
             collapsed_control_text: "".to_string(),
             footer: "".to_string(),
+            expander: None,
             buttons: vec![],
             default_button: 0,
             radio_buttons: vec![],
             default_radio_buttons: 0,
             main_icon: PCWSTR::null(),
             footer_icon: PCWSTR::null(),
+            custom_main_icon: None,
+            custom_footer_icon: None,
             dialog_hwnd: HWND::default(),
             is_destroyed: false,
+            is_expanded: false,
             hyperlink_callback: None,
             callback: None,
             cx_width: 0,
+            width: DialogWidth::Auto,
+            activation_context: false,
+            message_box_fallback: true,
+            handle: None,
+            event_sender: None,
         }
     }
 }
@@ -258,6 +350,9 @@ impl TaskDialogConfig {
             return;
         }
         self.expanded_information = expanded_information.to_string();
+        if let Some(expander) = self.expander.as_mut() {
+            expander.text = expanded_information.to_string();
+        }
         unsafe {
             let expanded_information_wchar = U16CString::from_str_unchecked(expanded_information);
             SendMessageA(
@@ -282,6 +377,88 @@ impl TaskDialogConfig {
                 LPARAM(if enable { 1 } else { 0 }),
             );
         }
+        if let Some(btn) = self.buttons.iter_mut().find(|b| b.id as usize == button_id) {
+            btn.elevation_required = enable;
+        }
+    }
+
+    /// Enable or disable the button
+    pub fn set_button_enabled(&mut self, button_id: usize, enable: bool) {
+        if self.dialog_hwnd.is_invalid() {
+            return;
+        }
+        unsafe {
+            SendMessageA(
+                self.dialog_hwnd,
+                TDM_ENABLE_BUTTON.0 as _,
+                WPARAM(button_id),
+                LPARAM(if enable { 1 } else { 0 }),
+            );
+        }
+        if let Some(btn) = self.buttons.iter_mut().find(|b| b.id as usize == button_id) {
+            btn.enabled = enable;
+        }
+        if let Some(handle) = &self.handle {
+            handle.button_enabled(button_id as _, enable);
+        }
+    }
+
+    /// Enable or disable the radio button
+    pub fn set_radio_button_enabled(&mut self, button_id: usize, enable: bool) {
+        if self.dialog_hwnd.is_invalid() {
+            return;
+        }
+        unsafe {
+            SendMessageA(
+                self.dialog_hwnd,
+                TDM_ENABLE_RADIO_BUTTON.0 as _,
+                WPARAM(button_id),
+                LPARAM(if enable { 1 } else { 0 }),
+            );
+        }
+        if let Some(btn) = self
+            .radio_buttons
+            .iter_mut()
+            .find(|b| b.id as usize == button_id)
+        {
+            btn.enabled = enable;
+        }
+    }
+
+    /// Set the main icon, a stock icon such as `TD_WARNING_ICON` or a `DialogIcon`.
+    ///
+    /// A dialog created with a stock `main_icon` cannot show a custom icon,
+    /// set `custom_main_icon` before showing the dialog to switch between
+    /// stock and custom icons.
+    pub fn set_main_icon<I: Into<DialogIcon>>(&mut self, icon: I) -> Result<(), Error> {
+        if self.dialog_hwnd.is_invalid() {
+            return Ok(());
+        }
+        update_icon(
+            self.dialog_hwnd,
+            TDIE_ICON_MAIN,
+            &mut self.main_icon,
+            &mut self.custom_main_icon,
+            icon.into(),
+        )
+    }
+
+    /// Set the footer icon, a stock icon such as `TD_WARNING_ICON` or a `DialogIcon`.
+    ///
+    /// A dialog created with a stock `footer_icon` cannot show a custom icon,
+    /// set `custom_footer_icon` before showing the dialog to switch between
+    /// stock and custom icons.
+    pub fn set_footer_icon<I: Into<DialogIcon>>(&mut self, icon: I) -> Result<(), Error> {
+        if self.dialog_hwnd.is_invalid() {
+            return Ok(());
+        }
+        update_icon(
+            self.dialog_hwnd,
+            TDIE_ICON_FOOTER,
+            &mut self.footer_icon,
+            &mut self.custom_footer_icon,
+            icon.into(),
+        )
     }
 
     /// Navigate to new page
@@ -289,6 +466,8 @@ impl TaskDialogConfig {
         if self.dialog_hwnd.is_invalid() {
             return;
         }
+        conf.dialog_hwnd = self.dialog_hwnd;
+        self.share_with_page(conf);
         execute_task_dialog(conf, ExecuteOption::TaskDialogNavigate).ok();
     }
 }
@@ -296,38 +475,200 @@ impl TaskDialogConfig {
 #[cfg(not(windows))]
 impl TaskDialogConfig {
     pub fn enable_process_bar(&mut self, _marquee: bool) {}
-    pub fn disable_process_bar(&mut self, marquee: bool) {}
+    pub fn disable_process_bar(&mut self, _marquee: bool) {}
     pub fn set_process_bar_marquee(&mut self, _enable: bool, _time: isize) {}
     pub fn set_process_bar(&mut self, _percentage: usize) {}
-    pub fn set_content(&mut self, content: &str) {}
-    pub fn set_main_instruction(&mut self, main_instruction: &str) {}
-    pub fn set_footer(&mut self, footer: &str) {}
-    pub fn set_expanded_information(&mut self, expanded_information: &str) {}
-    pub fn set_button_elevation_required_state(&mut self, button_id: usize, enable: bool) {}
-    pub fn navigate_page(&mut self, conf: &mut TaskDialogConfig) {}
+    pub fn set_content(&mut self, _content: &str) {}
+    pub fn set_main_instruction(&mut self, _main_instruction: &str) {}
+    pub fn set_footer(&mut self, _footer: &str) {}
+    pub fn set_expanded_information(&mut self, _expanded_information: &str) {}
+    pub fn set_button_elevation_required_state(&mut self, _button_id: usize, _enable: bool) {}
+    pub fn set_button_enabled(&mut self, _button_id: usize, _enable: bool) {}
+    pub fn set_radio_button_enabled(&mut self, _button_id: usize, _enable: bool) {}
+    pub fn set_main_icon<I: Into<DialogIcon>>(&mut self, _icon: I) -> Result<(), Error> {
+        Ok(())
+    }
+    pub fn set_footer_icon<I: Into<DialogIcon>>(&mut self, _icon: I) -> Result<(), Error> {
+        Ok(())
+    }
+    pub fn navigate_page(&mut self, _conf: &mut TaskDialogConfig) {}
 }
 
 pub struct TaskDialogButton {
     pub id: i32,
+    /// The button text, or the title line when `TDF_USE_COMMAND_LINKS` is set.
     pub text: String,
+    /// The secondary line shown below the title of a command link, ignored
+    /// without `TDF_USE_COMMAND_LINKS` or `TDF_USE_COMMAND_LINKS_NO_ICON`.
+    pub note: String,
+    /// Set to `false` to show the button disabled, default is `true`.
+    pub enabled: bool,
+    /// Show the UAC shield icon on the button, radio buttons have no shield.
+    pub elevation_required: bool,
+    /// Make this the default button, overrides `TaskDialogConfig::default_button`
+    /// or `TaskDialogConfig::default_radio_buttons`.
+    pub is_default: bool,
+    /// What happens when the button is clicked, ignored for radio buttons.
+    pub action: ButtonAction,
 }
 
-pub struct TaskDialogResult {
-    pub button_id: i32,
-    pub radio_button_id: i32,
-    pub checked: bool,
+/// The handler of `ButtonAction::KeepOpen`, called with the page the
+/// dialog shows.
+pub type ButtonHandler = Arc<dyn Fn(&mut TaskDialogConfig) + Send + Sync>;
+
+/// The action of a `TaskDialogButton`.
+pub enum ButtonAction {
+    /// Close the dialog, the default.
+    Close,
+    /// Call the handler and keep the dialog open, see `ButtonAction::keep_open`.
+    KeepOpen(ButtonHandler),
+    /// Navigate to the page and keep the dialog open.
+    Navigate(Box<TaskDialogConfig>),
+    /// Close the dialog and report the button ID as the clicked button.
+    CloseWith(i32),
+}
+
+impl ButtonAction {
+    /** Call `handler` and keep the dialog open, the handler can capture
+    the state it needs.
+
+    ```
+    use std::path::PathBuf;
+    use win_task_dialog::*;
+
+    let log_dir = PathBuf::from(r"C:\ProgramData\Editor\logs");
+    let open_logs = TaskDialogButton {
+        action: ButtonAction::keep_open(move |_conf| {
+            open_hyperlink(&log_dir.to_string_lossy());
+        }),
+        ..TaskDialogButton::new(100, "Open the log folder")
+    };
+    ```
+    */
+    pub fn keep_open<F>(handler: F) -> Self
+    where
+        F: Fn(&mut TaskDialogConfig) + Send + Sync + 'static,
+    {
+        ButtonAction::KeepOpen(Arc::new(handler))
+    }
+}
+
+impl Default for TaskDialogButton {
+    fn default() -> Self {
+        TaskDialogButton {
+            id: 0,
+            text: "".to_string(),
+            note: "".to_string(),
+            enabled: true,
+            elevation_required: false,
+            is_default: false,
+            action: ButtonAction::Close,
+        }
+    }
+}
+
+impl TaskDialogConfig {
+    /** Set the handle and the event sender of the dialog on the page it
+    navigates to. The values of the page are replaced, they belong to the
+    dialog which showed the page before, e.g. a previous `show_task_dialog`
+    of the same config. */
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn share_with_page(&self, page: &mut TaskDialogConfig) {
+        page.handle = self.handle.clone();
+        page.event_sender = self.event_sender.clone();
+    }
+}
+
+impl TaskDialogButton {
+    /** A button with the default state, change the other fields with the
+    struct update syntax.
+
+    ```
+    use win_task_dialog::*;
+
+    let save = TaskDialogButton::new(100, "Save");
+    let discard = TaskDialogButton {
+        note: "The changes are lost".to_string(),
+        ..TaskDialogButton::new(101, "Discard")
+    };
+    assert!(save.enabled && discard.enabled);
+    ```
+    */
+    pub fn new(id: i32, text: &str) -> Self {
+        TaskDialogButton {
+            id,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The text passed to the dialog, the note is appended to the title
+    /// as a second line when the buttons are command links.
+    #[cfg(windows)]
+    fn display_text(&self, flags: TASKDIALOG_FLAGS) -> String {
+        let command_links = flags & TDF_USE_COMMAND_LINKS == TDF_USE_COMMAND_LINKS
+            || flags & TDF_USE_COMMAND_LINKS_NO_ICON == TDF_USE_COMMAND_LINKS_NO_ICON;
+        if self.note.is_empty() || !command_links {
+            self.text.clone()
+        } else {
+            format!("{}\n{}", self.text, self.note)
+        }
+    }
+}
+
+/// Where the expanded information is shown.
+pub enum ExpanderPlacement {
+    /// Below the content text.
+    Content,
+    /// Below the footer text.
+    Footer,
+}
+
+/// The expandable area of the dialog.
+pub struct Expander {
+    /// The expanded information text.
+    pub text: String,
+    /// The expando button label when the area is expanded.
+    pub expanded_label: String,
+    /// The expando button label when the area is collapsed.
+    pub collapsed_label: String,
+    pub initially_expanded: bool,
+    pub placement: ExpanderPlacement,
 }
 
-impl Default for TaskDialogResult {
+impl Default for Expander {
     fn default() -> Self {
-        TaskDialogResult {
-            button_id: 0,
-            radio_button_id: 0,
-            checked: false,
+        Expander {
+            text: "".to_string(),
+            expanded_label: "".to_string(),
+            collapsed_label: "".to_string(),
+            initially_expanded: false,
+            placement: ExpanderPlacement::Content,
         }
     }
 }
 
+/// How the dialog was shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DialogRenderer {
+    #[default]
+    TaskDialog,
+    /// comctl32 version 6 is not loaded, the dialog was shown by `MessageBoxW`.
+    MessageBox,
+}
+
+#[derive(Clone, Default)]
+pub struct TaskDialogResult {
+    pub button_id: i32,
+    pub radio_button_id: i32,
+    pub checked: bool,
+    /// The expanded state of the expandable area when the dialog closed.
+    pub expanded: bool,
+    pub renderer: DialogRenderer,
+    /// What the `MessageBoxW` rendering could not show, empty for a task dialog.
+    pub fallback_warnings: Vec<String>,
+}
+
 /** Show task dialog */
 #[cfg(windows)]
 pub fn show_task_dialog(conf: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error> {
@@ -342,6 +683,27 @@ pub fn execute_task_dialog(
 ) -> Result<TaskDialogResult, Error> {
     use std::ptr::addr_of_mut;
 
+    // Activated before comctl32 is loaded, and kept until the dialog is closed.
+    let _activation = match opt {
+        ExecuteOption::TaskDialogIndirect if conf.activation_context => {
+            comctl::ActivationScope::activate()
+        }
+        _ => None,
+    };
+    let task_dialog_indirect = match opt {
+        ExecuteOption::TaskDialogIndirect => match comctl::task_dialog_indirect() {
+            Some(task_dialog_indirect) => Some(task_dialog_indirect),
+            None if conf.message_box_fallback => return show_message_box_fallback(conf),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "TaskDialogIndirect needs comctl32 version 6",
+                ))
+            }
+        },
+        ExecuteOption::TaskDialogNavigate => None,
+    };
+
     let mut result = TaskDialogResult::default();
     let conf_ptr: *mut TaskDialogConfig = conf;
     let conf_long_ptr = conf_ptr as isize;
@@ -364,19 +726,36 @@ pub fn execute_task_dialog(
         let main_instruction: U16CString = U16CString::from_str_unchecked(&conf.main_instruction);
         let content: U16CString = U16CString::from_str_unchecked(&conf.content);
         let verification_text: U16CString = U16CString::from_str_unchecked(&conf.verification_text);
-        let expanded_information: U16CString =
-            U16CString::from_str_unchecked(&conf.expanded_information);
-        let expanded_control_text: U16CString =
-            U16CString::from_str_unchecked(&conf.expanded_control_text);
-        let collapsed_control_text: U16CString =
-            U16CString::from_str_unchecked(&conf.collapsed_control_text);
         let footer: U16CString = U16CString::from_str_unchecked(&conf.footer);
 
+        // Expandable area
+        let mut flags = conf.flags;
+        let expanded_information: U16CString;
+        let expanded_control_text: U16CString;
+        let collapsed_control_text: U16CString;
+        if let Some(expander) = &conf.expander {
+            expanded_information = U16CString::from_str_unchecked(&expander.text);
+            expanded_control_text = U16CString::from_str_unchecked(&expander.expanded_label);
+            collapsed_control_text = U16CString::from_str_unchecked(&expander.collapsed_label);
+            flags &= !(TDF_EXPANDED_BY_DEFAULT | TDF_EXPAND_FOOTER_AREA);
+            if expander.initially_expanded {
+                flags |= TDF_EXPANDED_BY_DEFAULT;
+            }
+            if let ExpanderPlacement::Footer = expander.placement {
+                flags |= TDF_EXPAND_FOOTER_AREA;
+            }
+        } else {
+            expanded_information = U16CString::from_str_unchecked(&conf.expanded_information);
+            expanded_control_text = U16CString::from_str_unchecked(&conf.expanded_control_text);
+            collapsed_control_text = U16CString::from_str_unchecked(&conf.collapsed_control_text);
+        }
+        conf.is_expanded = flags & TDF_EXPANDED_BY_DEFAULT == TDF_EXPANDED_BY_DEFAULT;
+
         // Buttons
         let btn_text: Vec<U16CString> = conf
             .buttons
             .iter()
-            .map(|btn| U16CString::from_str_unchecked(&btn.text))
+            .map(|btn| U16CString::from_str_unchecked(btn.display_text(flags)))
             .collect();
         let buttons: Vec<TASKDIALOG_BUTTON> = conf
             .buttons
@@ -407,13 +786,24 @@ pub fn execute_task_dialog(
         // ICON
         let mut u1: TASKDIALOGCONFIG_0 = Default::default();
         let mut u2: TASKDIALOGCONFIG_1 = Default::default();
-        if !conf.main_icon.is_null() {
+        if let Some(icon) = conf.custom_main_icon.as_mut() {
+            flags |= TDF_USE_HICON_MAIN;
+            u1.hMainIcon = icon.main_handle()?;
+        } else if !conf.main_icon.is_null() {
             u1.pszMainIcon = conf.main_icon;
         }
-        if !conf.footer_icon.is_null() {
+        if let Some(icon) = conf.custom_footer_icon.as_mut() {
+            flags |= TDF_USE_HICON_FOOTER;
+            u2.hFooterIcon = icon.footer_handle()?;
+        } else if !conf.footer_icon.is_null() {
             u2.pszFooterIcon = conf.footer_icon;
         }
 
+        let cx_width = match conf.width {
+            DialogWidth::Auto => conf.cx_width,
+            width => width.to_dialog_units(&FontMetrics::for_window(conf.parent)?),
+        };
+
         unsafe extern "system" fn callback(
             hwnd: HWND,
             msg: TASKDIALOG_NOTIFICATIONS,
@@ -423,16 +813,54 @@ pub fn execute_task_dialog(
         ) -> HRESULT {
             use windows::Win32::{
                 Foundation::S_OK,
-                UI::Controls::{TDN_CREATED, TDN_DESTROYED, TDN_HYPERLINK_CLICKED},
+                UI::Controls::{
+                    TDN_BUTTON_CLICKED, TDN_CREATED, TDN_DESTROYED, TDN_EXPANDO_BUTTON_CLICKED,
+                    TDN_HYPERLINK_CLICKED, TDN_NAVIGATED, TDN_RADIO_BUTTON_CLICKED, TDN_TIMER,
+                    TDN_VERIFICATION_CLICKED,
+                },
             };
 
             let conf = std::mem::transmute::<isize, *mut TaskDialogConfig>(lp_ref_data);
+            let active = active_page(hwnd);
             match msg {
                 TDN_CREATED => {
                     (*conf).dialog_hwnd = hwnd;
+                    apply_button_states(hwnd, &*conf);
+                    if let Some(handle) = &(*conf).handle {
+                        handle.attach(hwnd, &*conf);
+                    }
+                    (*conf).emit(TaskDialogEvent::Created);
+                }
+                TDN_NAVIGATED => {
+                    if let Some(active) = active {
+                        active.page.set(conf);
+                        (*active.root).is_expanded = (*conf).is_expanded;
+                    }
+                    apply_button_states(hwnd, &*conf);
+                    if let Some(handle) = &(*conf).handle {
+                        handle.attach(hwnd, &*conf);
+                    }
+                    (*conf).emit(TaskDialogEvent::Navigated);
                 }
                 TDN_DESTROYED => {
                     (*conf).is_destroyed = true;
+                    if let Some(active) = active {
+                        use windows::Win32::UI::WindowsAndMessaging::RemovePropW;
+
+                        (*active.root).is_destroyed = true;
+                        RemovePropW(hwnd, ACTIVE_PAGE_PROP).ok();
+                    }
+                    if let Some(handle) = &(*conf).handle {
+                        handle.detach();
+                    }
+                    (*conf).emit(TaskDialogEvent::Destroyed);
+                }
+                TDN_EXPANDO_BUTTON_CLICKED => {
+                    (*conf).is_expanded = _w_param.0 != 0;
+                    if let Some(active) = active {
+                        (*active.root).is_expanded = _w_param.0 != 0;
+                    }
+                    (*conf).emit(TaskDialogEvent::ExpanderToggled(_w_param.0 != 0));
                 }
                 TDN_HYPERLINK_CLICKED => {
                     let link = U16CString::from_ptr_str(_l_param.0 as *const u16)
@@ -441,21 +869,78 @@ pub fn execute_task_dialog(
                     if let Some(callback) = (*conf).hyperlink_callback {
                         callback(&link);
                     }
+                    (*conf).emit(TaskDialogEvent::HyperlinkClicked(link));
+                }
+                TDN_RADIO_BUTTON_CLICKED => {
+                    (*conf).emit(TaskDialogEvent::RadioButtonClicked(_w_param.0 as i32));
+                }
+                TDN_VERIFICATION_CLICKED => {
+                    (*conf).emit(TaskDialogEvent::VerificationClicked(_w_param.0 != 0));
+                }
+                TDN_TIMER => {
+                    (*conf).emit(TaskDialogEvent::Timer(_w_param.0 as u32));
                 }
                 _ => {}
             };
-            if let Some(callback) = (*conf).callback {
-                return callback(hwnd, msg, _w_param, _l_param, lp_ref_data as _);
+            // A close requested through the handle cannot be vetoed, and the
+            // button clicked to close the dialog is not reported to the callback
+            let closing = msg == TDN_BUTTON_CLICKED
+                && (*conf)
+                    .handle
+                    .as_ref()
+                    .is_some_and(|handle| handle.close_outcome().is_some());
+            let ret = match (*conf).callback {
+                Some(callback) if !closing => {
+                    callback(hwnd, msg, _w_param, _l_param, lp_ref_data as _)
+                }
+                _ => S_OK,
+            };
+            if msg != TDN_BUTTON_CLICKED {
+                return ret;
             }
 
-            S_OK
+            let button_id = _w_param.0 as i32;
+            let action = if closing || ret != S_OK {
+                None
+            } else {
+                (*conf)
+                    .buttons
+                    .iter_mut()
+                    .find(|btn| btn.id == button_id)
+                    .map(|btn| &mut btn.action)
+            };
+            let keep_open = matches!(
+                action,
+                Some(ButtonAction::KeepOpen(_)) | Some(ButtonAction::Navigate(_))
+            );
+            let vetoed = !closing && (ret != S_OK || keep_open);
+            (*conf).emit(TaskDialogEvent::ButtonClicked { button_id, vetoed });
+            if closing {
+                return S_OK;
+            }
+
+            // Run the action of the clicked button, unless the callback kept the dialog open
+            match action {
+                Some(ButtonAction::KeepOpen(handler)) => {
+                    // The handler may replace the buttons it belongs to
+                    let handler = handler.clone();
+                    handler(&mut *conf);
+                    S_FALSE
+                }
+                Some(ButtonAction::Navigate(page)) => {
+                    let page: *mut TaskDialogConfig = &mut **page;
+                    (*conf).navigate_page(&mut *page);
+                    S_FALSE
+                }
+                _ => ret,
+            }
         }
 
         let mut config = TASKDIALOGCONFIG {
-            cbSize: std::mem::size_of::<TASKDIALOGCONFIG>() as UINT,
+            cbSize: std::mem::size_of::<TASKDIALOGCONFIG>() as u32,
             hwndParent: conf.parent,
             hInstance: instance.into(),
-            dwFlags: conf.flags,
+            dwFlags: flags,
             dwCommonButtons: conf.common_buttons,
             pszWindowTitle: PCWSTR::from_raw(window_title.as_ptr()),
             pszMainInstruction: PCWSTR::from_raw(main_instruction.as_ptr()),
@@ -465,35 +950,71 @@ pub fn execute_task_dialog(
             pszExpandedControlText: PCWSTR::from_raw(expanded_control_text.as_ptr()),
             pszCollapsedControlText: PCWSTR::from_raw(collapsed_control_text.as_ptr()),
             pszFooter: PCWSTR::from_raw(footer.as_ptr()),
-            cButtons: buttons.len() as UINT,
+            cButtons: buttons.len() as u32,
             pButtons: buttons.as_slice().as_ptr(),
-            nDefaultButton: conf.default_button,
-            cRadioButtons: radio_buttons.len() as UINT,
+            nDefaultButton: conf
+                .buttons
+                .iter()
+                .find(|btn| btn.is_default)
+                .map_or(conf.default_button, |btn| btn.id),
+            cRadioButtons: radio_buttons.len() as u32,
             pRadioButtons: radio_buttons.as_slice().as_ptr(),
-            nDefaultRadioButton: conf.default_radio_buttons,
+            nDefaultRadioButton: conf
+                .radio_buttons
+                .iter()
+                .find(|btn| btn.is_default)
+                .map_or(conf.default_radio_buttons, |btn| btn.id),
             Anonymous1: u1,
             Anonymous2: u2,
             pfCallback: Some(callback),
             lpCallbackData: conf_long_ptr,
-            cxWidth: conf.cx_width,
+            cxWidth: cx_width,
         };
 
-        match opt {
-            ExecuteOption::TaskDialogIndirect => {
+        match task_dialog_indirect {
+            Some(task_dialog_indirect) => {
+                // Every shown dialog has a handle, to be closed by `close` and the registry
+                let owned_handle = conf.handle.is_none();
+                let handle = conf
+                    .handle
+                    .get_or_insert_with(TaskDialogHandle::new)
+                    .clone();
+                handle.begin();
+                DialogRegistry::global().register(&handle);
+
                 // Result
+                let active = ActivePage {
+                    root: conf_ptr,
+                    page: std::cell::Cell::new(conf_ptr),
+                };
+                CREATING_PAGE.with(|creating| creating.set(&active as *const ActivePage as isize));
                 let mut verify: BOOL = FALSE;
-                let dialog_result = TaskDialogIndirect(
+                let dialog_result = task_dialog_indirect(
                     &config,
-                    Some(&mut result.button_id),
-                    Some(&mut result.radio_button_id),
-                    Some(&mut verify),
+                    &mut result.button_id,
+                    &mut result.radio_button_id,
+                    &mut verify,
                 )
-                .map_or_else(|e| e.code().0, |_| 0);
+                .0;
+                CREATING_PAGE.with(|creating| creating.set(0));
                 result.checked = verify != FALSE;
+                result.expanded = conf.is_expanded;
+                // The page the dialog showed when it closed
+                let page = active.page.get();
+                if let Some(button_id) = close_with(&(*page).buttons, result.button_id) {
+                    result.button_id = button_id;
+                }
+                if let Some(outcome) = handle.finish() {
+                    result.button_id = outcome;
+                }
+                DialogRegistry::global().unregister(&handle);
+                if owned_handle {
+                    conf.handle = None;
+                }
 
                 dialog_result
             }
-            ExecuteOption::TaskDialogNavigate => {
+            None => {
                 SendMessageA(
                     conf.dialog_hwnd,
                     TDM_NAVIGATE_PAGE.0 as _,
@@ -513,6 +1034,134 @@ pub fn execute_task_dialog(
     }
 }
 
+/** Apply the `enabled` and `elevation_required` state of the buttons and
+radio buttons, the dialog has no way to set them at creation. */
+#[cfg(windows)]
+unsafe fn apply_button_states(hwnd: HWND, conf: &TaskDialogConfig) {
+    for btn in conf.buttons.iter() {
+        if !btn.enabled {
+            SendMessageA(
+                hwnd,
+                TDM_ENABLE_BUTTON.0 as _,
+                WPARAM(btn.id as _),
+                LPARAM(0),
+            );
+        }
+        if btn.elevation_required {
+            SendMessageA(
+                hwnd,
+                TDM_SET_BUTTON_ELEVATION_REQUIRED_STATE.0 as _,
+                WPARAM(btn.id as _),
+                LPARAM(1),
+            );
+        }
+    }
+    for btn in conf.radio_buttons.iter() {
+        if !btn.enabled {
+            SendMessageA(
+                hwnd,
+                TDM_ENABLE_RADIO_BUTTON.0 as _,
+                WPARAM(btn.id as _),
+                LPARAM(0),
+            );
+        }
+    }
+}
+
+/** Find the `ButtonAction::CloseWith` of the clicked button, the buttons
+are the buttons of the page the dialog showed when it closed. */
+#[cfg(windows)]
+fn close_with(buttons: &[TaskDialogButton], button_id: i32) -> Option<i32> {
+    buttons.iter().find_map(|btn| match &btn.action {
+        ButtonAction::CloseWith(id) if btn.id == button_id => Some(*id),
+        _ => None,
+    })
+}
+
+/// The window property of the dialog which points to its `ActivePage`.
+#[cfg(windows)]
+const ACTIVE_PAGE_PROP: PCWSTR = windows::core::w!("win-task-dialog-active-page");
+
+#[cfg(windows)]
+thread_local! {
+    /// The `ActivePage` of the dialog being created on this thread, 0 when none.
+    static CREATING_PAGE: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+/** The config the dialog was shown with, and the page it shows. The pages
+the dialog navigates to are the configs of `navigate_page`, the callback
+data of the dialog points to the page it shows. */
+#[cfg(windows)]
+struct ActivePage {
+    root: *mut TaskDialogConfig,
+    page: std::cell::Cell<*mut TaskDialogConfig>,
+}
+
+/// The `ActivePage` of the dialog, attached to the window on the first
+/// notification of the dialog.
+#[cfg(windows)]
+unsafe fn active_page<'a>(hwnd: HWND) -> Option<&'a ActivePage> {
+    use windows::Win32::{
+        Foundation::HANDLE,
+        UI::WindowsAndMessaging::{GetPropW, SetPropW},
+    };
+
+    let mut data = GetPropW(hwnd, ACTIVE_PAGE_PROP);
+    if data.is_invalid() {
+        let creating = CREATING_PAGE.with(|creating| creating.replace(0));
+        if creating == 0 {
+            return None;
+        }
+        data = HANDLE(creating as _);
+        SetPropW(hwnd, ACTIVE_PAGE_PROP, Some(data)).ok()?;
+    }
+    Some(&*(data.0 as *const ActivePage))
+}
+
+/** Send `TDM_UPDATE_ICON` and keep the icon fields of the config in sync,
+the icon must match the kind of icon the dialog was created with. */
+#[cfg(windows)]
+fn update_icon(
+    hwnd: HWND,
+    element: TASKDIALOG_ICON_ELEMENTS,
+    stock: &mut PCWSTR,
+    custom: &mut Option<DialogIcon>,
+    mut icon: DialogIcon,
+) -> Result<(), Error> {
+    let footer = element == TDIE_ICON_FOOTER;
+    let l_param = if custom.is_some() {
+        let handle = if footer {
+            icon.footer_handle()?
+        } else {
+            icon.main_handle()?
+        };
+        LPARAM(handle.0 as _)
+    } else if let Some(icon) = icon.stock() {
+        LPARAM(icon.0 as _)
+    } else {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "the dialog was created with a stock icon and cannot show a custom icon",
+        ));
+    };
+    unsafe {
+        SendMessageA(
+            hwnd,
+            TDM_UPDATE_ICON.0 as _,
+            WPARAM(element.0 as _),
+            l_param,
+        );
+    }
+    if let Some(icon) = icon.stock() {
+        *stock = icon;
+    }
+    if custom.is_some() {
+        // Dropping the previous icon destroys its HICON, which is no longer shown.
+        *custom = Some(icon);
+    }
+    Ok(())
+}
+
 /** Show message dialog, the dialog have only the OK button */
 #[cfg(windows)]
 pub fn show_msg_dialog(
@@ -533,7 +1182,7 @@ pub fn show_msg_dialog(
 }
 
 #[cfg(not(windows))]
-pub fn show_task_dialog(_conf: &TaskDialogConfig) -> Result<TaskDialogResult, Error> {
+pub fn show_task_dialog(_conf: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error> {
     Ok(TaskDialogResult::default())
 }
 
@@ -542,7 +1191,49 @@ pub fn show_msg_dialog(
     _title: &str,
     _main_instruction: &str,
     _content: &str,
-    _icon: *mut u16,
+    _icon: PCWSTR,
 ) -> Option<Error> {
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn pages_follow_the_dialog_which_shows_them() {
+        fn show_and_navigate(conf: &mut TaskDialogConfig) {
+            // What `execute_task_dialog` sets for each show
+            let handle = TaskDialogHandle::new();
+            let (sender, events) = channel();
+            conf.handle = Some(handle.clone());
+            conf.event_sender = Some(sender);
+
+            let mut page = match std::mem::replace(&mut conf.buttons[0].action, ButtonAction::Close)
+            {
+                ButtonAction::Navigate(page) => page,
+                _ => unreachable!(),
+            };
+            conf.share_with_page(&mut page);
+            assert!(page.handle.as_ref().unwrap().same_dialog(&handle));
+            page.emit(TaskDialogEvent::Navigated);
+            assert!(matches!(events.try_recv(), Ok(TaskDialogEvent::Navigated)));
+            conf.buttons[0].action = ButtonAction::Navigate(page);
+
+            conf.handle = None;
+            conf.event_sender = None;
+        }
+
+        let mut conf = TaskDialogConfig {
+            buttons: vec![TaskDialogButton {
+                action: ButtonAction::Navigate(Box::default()),
+                ..TaskDialogButton::new(100, "Next")
+            }],
+            ..Default::default()
+        };
+        // The second show replaces the handle and the sender of the first
+        show_and_navigate(&mut conf);
+        show_and_navigate(&mut conf);
+    }
+}
@@ -0,0 +1,852 @@
+use crate::*;
+use std::ops::{BitOr, BitOrAssign};
+
+/// The `MB_*` style of `message_box`, combine the flags with `|`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MessageBoxStyle(pub u32);
+
+impl BitOr for MessageBoxStyle {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        MessageBoxStyle(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MessageBoxStyle {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl MessageBoxStyle {
+    fn has(self, flag: MessageBoxStyle) -> bool {
+        self.0 & flag.0 == flag.0
+    }
+}
+
+/// The ID of the clicked button, e.g. `IDYES`, or 0 when the dialog
+/// could not be shown, like the return value of `MessageBoxW`.
+pub type MessageBoxResult = i32;
+
+const MB_TYPEMASK: u32 = 0x0000_000F;
+const MB_ICONMASK: u32 = 0x0000_00F0;
+const MB_DEFMASK: u32 = 0x0000_0F00;
+
+pub const MB_OK: MessageBoxStyle = MessageBoxStyle(0x0000_0000);
+pub const MB_OKCANCEL: MessageBoxStyle = MessageBoxStyle(0x0000_0001);
+pub const MB_ABORTRETRYIGNORE: MessageBoxStyle = MessageBoxStyle(0x0000_0002);
+pub const MB_YESNOCANCEL: MessageBoxStyle = MessageBoxStyle(0x0000_0003);
+pub const MB_YESNO: MessageBoxStyle = MessageBoxStyle(0x0000_0004);
+pub const MB_RETRYCANCEL: MessageBoxStyle = MessageBoxStyle(0x0000_0005);
+pub const MB_CANCELTRYCONTINUE: MessageBoxStyle = MessageBoxStyle(0x0000_0006);
+pub const MB_ICONHAND: MessageBoxStyle = MessageBoxStyle(0x0000_0010);
+pub const MB_ICONQUESTION: MessageBoxStyle = MessageBoxStyle(0x0000_0020);
+pub const MB_ICONEXCLAMATION: MessageBoxStyle = MessageBoxStyle(0x0000_0030);
+pub const MB_ICONASTERISK: MessageBoxStyle = MessageBoxStyle(0x0000_0040);
+pub const MB_ICONERROR: MessageBoxStyle = MB_ICONHAND;
+pub const MB_ICONSTOP: MessageBoxStyle = MB_ICONHAND;
+pub const MB_ICONWARNING: MessageBoxStyle = MB_ICONEXCLAMATION;
+pub const MB_ICONINFORMATION: MessageBoxStyle = MB_ICONASTERISK;
+pub const MB_DEFBUTTON1: MessageBoxStyle = MessageBoxStyle(0x0000_0000);
+pub const MB_DEFBUTTON2: MessageBoxStyle = MessageBoxStyle(0x0000_0100);
+pub const MB_DEFBUTTON3: MessageBoxStyle = MessageBoxStyle(0x0000_0200);
+pub const MB_DEFBUTTON4: MessageBoxStyle = MessageBoxStyle(0x0000_0300);
+pub const MB_APPLMODAL: MessageBoxStyle = MessageBoxStyle(0x0000_0000);
+pub const MB_SYSTEMMODAL: MessageBoxStyle = MessageBoxStyle(0x0000_1000);
+pub const MB_TASKMODAL: MessageBoxStyle = MessageBoxStyle(0x0000_2000);
+pub const MB_SETFOREGROUND: MessageBoxStyle = MessageBoxStyle(0x0001_0000);
+pub const MB_TOPMOST: MessageBoxStyle = MessageBoxStyle(0x0004_0000);
+pub const MB_RIGHT: MessageBoxStyle = MessageBoxStyle(0x0008_0000);
+pub const MB_RTLREADING: MessageBoxStyle = MessageBoxStyle(0x0010_0000);
+
+/// `IDI_QUESTION`, task dialogs have no stock question icon.
+const IDI_QUESTION: usize = 32514;
+
+/// The button IDs of the button type, in the order `MessageBoxW` shows them.
+fn button_ids(style: MessageBoxStyle) -> &'static [i32] {
+    match MessageBoxStyle(style.0 & MB_TYPEMASK) {
+        MB_OKCANCEL => &[IDOK, IDCANCEL],
+        MB_ABORTRETRYIGNORE => &[IDABORT, IDRETRY, IDIGNORE],
+        MB_YESNOCANCEL => &[IDYES, IDNO, IDCANCEL],
+        MB_YESNO => &[IDYES, IDNO],
+        MB_RETRYCANCEL => &[IDRETRY, IDCANCEL],
+        MB_CANCELTRYCONTINUE => &[IDCANCEL, IDTRYAGAIN, IDCONTINUE],
+        _ => &[IDOK],
+    }
+}
+
+/** Translate the arguments of `MessageBoxW` into a task dialog.
+
+The caption is the window title and the text is the content, an empty
+caption uses the window title of `set_dialog_defaults`. `MB_TOPMOST` and
+`MB_SYSTEMMODAL` keep the dialog above other windows, `MB_RTLREADING`
+sets `TDF_RTL_LAYOUT`, the other modality flags, `MB_RIGHT` and
+`MB_SETFOREGROUND` have no effect.
+
+The Abort, Ignore, Try Again and Continue buttons are no common buttons
+of the task dialog, they are added as buttons with English text.
+
+```
+use win_task_dialog::*;
+
+let conf = message_box_config(
+    HWND::default(),
+    "Save the changes?",
+    "Editor",
+    MB_YESNOCANCEL | MB_ICONWARNING | MB_DEFBUTTON2,
+);
+assert_eq!(conf.common_buttons, TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON);
+assert_eq!(conf.main_icon, TD_WARNING_ICON);
+assert_eq!(conf.default_button, IDNO);
+```
+*/
+pub fn message_box_config(
+    parent: HWND,
+    text: &str,
+    caption: &str,
+    style: MessageBoxStyle,
+) -> TaskDialogConfig {
+    let mut conf = TaskDialogConfig::from_defaults();
+    conf.parent = parent;
+    if !caption.is_empty() {
+        conf.window_title = caption.to_string();
+    }
+    conf.content = text.to_string();
+
+    conf.common_buttons = match MessageBoxStyle(style.0 & MB_TYPEMASK) {
+        MB_OKCANCEL => TDCBF_OK_BUTTON | TDCBF_CANCEL_BUTTON,
+        MB_YESNOCANCEL => TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON,
+        MB_YESNO => TDCBF_YES_BUTTON | TDCBF_NO_BUTTON,
+        MB_RETRYCANCEL => TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON,
+        // The dialog shows the buttons before the common buttons, so all
+        // of them are buttons to keep the order of `MessageBoxW`.
+        MB_ABORTRETRYIGNORE => {
+            conf.buttons = vec![
+                TaskDialogButton::new(IDABORT, "&Abort"),
+                TaskDialogButton::new(IDRETRY, "&Retry"),
+                TaskDialogButton::new(IDIGNORE, "&Ignore"),
+            ];
+            TASKDIALOG_COMMON_BUTTON_FLAGS::default()
+        }
+        MB_CANCELTRYCONTINUE => {
+            conf.buttons = vec![
+                TaskDialogButton::new(IDCANCEL, "Cancel"),
+                TaskDialogButton::new(IDTRYAGAIN, "&Try Again"),
+                TaskDialogButton::new(IDCONTINUE, "&Continue"),
+            ];
+            conf.flags |= TDF_ALLOW_DIALOG_CANCELLATION;
+            TASKDIALOG_COMMON_BUTTON_FLAGS::default()
+        }
+        _ => {
+            // Escape closes a message box with only an OK button.
+            conf.flags |= TDF_ALLOW_DIALOG_CANCELLATION;
+            TDCBF_OK_BUTTON
+        }
+    };
+
+    // MB_DEFBUTTON1 and a default button beyond the last button select the first button.
+    let ids = button_ids(style);
+    let default = ((style.0 & MB_DEFMASK) >> 8) as usize;
+    conf.default_button = *ids.get(default).unwrap_or(&ids[0]);
+
+    match MessageBoxStyle(style.0 & MB_ICONMASK) {
+        MB_ICONHAND => conf.main_icon = TD_ERROR_ICON,
+        MB_ICONEXCLAMATION => conf.main_icon = TD_WARNING_ICON,
+        MB_ICONASTERISK => conf.main_icon = TD_INFORMATION_ICON,
+        MB_ICONQUESTION => {
+            conf.custom_main_icon = Some(DialogIcon::from_stock(PCWSTR(IDI_QUESTION as *const u16)))
+        }
+        _ => {}
+    }
+
+    if style.has(MB_RTLREADING) {
+        conf.flags |= TDF_RTL_LAYOUT;
+    }
+    if style.has(MB_TOPMOST) || style.has(MB_SYSTEMMODAL) {
+        conf.callback = Some(topmost_callback);
+    }
+
+    conf
+}
+
+/** Show a task dialog in place of `MessageBoxW`, see `message_box_config`
+for the supported styles. */
+pub fn message_box(
+    parent: HWND,
+    text: &str,
+    caption: &str,
+    style: MessageBoxStyle,
+) -> MessageBoxResult {
+    let mut conf = message_box_config(parent, text, caption, style);
+    match show_task_dialog(&mut conf) {
+        // Closing a message box with only an OK button returns IDOK.
+        Ok(_) if button_ids(style) == [IDOK] => IDOK,
+        Ok(result) => result.button_id,
+        Err(_) => 0,
+    }
+}
+
+/// The arguments of `MessageBoxW` which show a task dialog, see `message_box_fallback`.
+pub struct MessageBoxFallback {
+    pub text: String,
+    pub caption: String,
+    pub style: MessageBoxStyle,
+    /// The IDs returned by `MessageBoxW` which report another button of the
+    /// dialog, e.g. `IDOK` for a collapsed button.
+    pub button_map: Vec<(i32, i32)>,
+    /// What the message box cannot show.
+    pub warnings: Vec<String>,
+}
+
+impl MessageBoxFallback {
+    /// The button ID of the dialog for the return value of `MessageBoxW`.
+    pub fn button_id(&self, result: MessageBoxResult) -> i32 {
+        self.button_map
+            .iter()
+            .find(|(id, _)| *id == result)
+            .map_or(result, |(_, button_id)| *button_id)
+    }
+}
+
+/// The button IDs which `MessageBoxW` can show.
+const MESSAGE_BOX_IDS: [i32; 10] = [
+    IDOK, IDCANCEL, IDABORT, IDRETRY, IDIGNORE, IDYES, IDNO, IDCLOSE, IDTRYAGAIN, IDCONTINUE,
+];
+
+/// Remove the `<a>` tags of `TDF_ENABLE_HYPERLINKS` text, keeping the link text.
+fn strip_links(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let tag = &rest[start..];
+        let end = if tag.starts_with("<a ") || tag.starts_with("<a>") || tag.starts_with("</a>") {
+            tag.find('>')
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                stripped.push_str(&rest[..start]);
+                rest = &tag[end + 1..];
+            }
+            None => {
+                stripped.push_str(&rest[..start + 1]);
+                rest = &tag[1..];
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/** Translate a task dialog into the arguments of `MessageBoxW`, the best-effort
+inverse of `message_box_config`, used when comctl32 version 6 is not loaded.
+
+The text is the main instruction and the content. Buttons with an ID of
+`MessageBoxW`, such as `IDYES`, keep their ID. Other buttons are collapsed
+into OK, which reports the default or first of them, and Cancel, which
+reports the next one when the dialog cannot be cancelled. Everything
+the message box cannot show is listed in `warnings`.
+
+```
+use win_task_dialog::*;
+
+let conf = TaskDialogConfig {
+    main_instruction: "Save the changes?".to_owned(),
+    buttons: vec![TaskDialogButton::new(100, "Save")],
+    ..Default::default()
+};
+let fallback = message_box_fallback(&conf);
+assert_eq!(fallback.style, MB_OKCANCEL);
+assert_eq!(fallback.button_id(IDOK), 100);
+assert_eq!(fallback.button_id(IDCANCEL), IDCANCEL);
+```
+*/
+pub fn message_box_fallback(conf: &TaskDialogConfig) -> MessageBoxFallback {
+    let hyperlinks = conf.flags & TDF_ENABLE_HYPERLINKS == TDF_ENABLE_HYPERLINKS;
+    let text = [&conf.main_instruction, &conf.content]
+        .iter()
+        .filter(|text| !text.is_empty())
+        .map(|text| {
+            if hyperlinks {
+                strip_links(text)
+            } else {
+                text.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // The buttons in the order of the dialog, common buttons follow the buttons.
+    let mut buttons: Vec<(i32, String)> = conf
+        .buttons
+        .iter()
+        .map(|btn| (btn.id, btn.text.clone()))
+        .collect();
+    for (flag, id, text) in [
+        (TDCBF_OK_BUTTON, IDOK, "OK"),
+        (TDCBF_YES_BUTTON, IDYES, "Yes"),
+        (TDCBF_NO_BUTTON, IDNO, "No"),
+        (TDCBF_CANCEL_BUTTON, IDCANCEL, "Cancel"),
+        (TDCBF_RETRY_BUTTON, IDRETRY, "Retry"),
+        (TDCBF_CLOSE_BUTTON, IDCLOSE, "Close"),
+    ] {
+        if conf.common_buttons & flag == flag {
+            buttons.push((id, text.to_string()));
+        }
+    }
+    let has = |id: i32| buttons.iter().any(|(button_id, _)| *button_id == id);
+
+    // The Close button is shown as Cancel.
+    let cancel_id = if has(IDCANCEL) {
+        Some(IDCANCEL)
+    } else if has(IDCLOSE) {
+        Some(IDCLOSE)
+    } else {
+        None
+    };
+    let default_id = conf
+        .buttons
+        .iter()
+        .find(|btn| btn.is_default)
+        .map_or(conf.default_button, |btn| btn.id);
+
+    let mut button_map = vec![];
+    let style = if has(IDABORT) && has(IDRETRY) && has(IDIGNORE) {
+        MB_ABORTRETRYIGNORE
+    } else if has(IDTRYAGAIN) && has(IDCONTINUE) {
+        MB_CANCELTRYCONTINUE
+    } else if has(IDYES) && has(IDNO) {
+        if cancel_id.is_some() {
+            MB_YESNOCANCEL
+        } else {
+            MB_YESNO
+        }
+    } else if has(IDRETRY) && cancel_id.is_some() && !has(IDOK) {
+        MB_RETRYCANCEL
+    } else {
+        // Collapse the other buttons into OK.
+        let collapsed = buttons
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| Some(*id) != cancel_id && *id != IDCANCEL && *id != IDCLOSE)
+            .collect::<Vec<_>>();
+        let ok_id = if has(IDOK) {
+            Some(IDOK)
+        } else if collapsed.contains(&default_id) {
+            Some(default_id)
+        } else {
+            collapsed.first().copied()
+        };
+        let second_id = collapsed.iter().copied().find(|id| Some(*id) != ok_id);
+        match ok_id {
+            Some(ok_id) if cancel_id.is_some() => {
+                button_map.push((IDOK, ok_id));
+                MB_OKCANCEL
+            }
+            Some(ok_id) => {
+                button_map.push((IDOK, ok_id));
+                // Without a way to cancel the dialog, Cancel reports the next button.
+                match second_id {
+                    Some(second_id) => {
+                        button_map.push((IDCANCEL, second_id));
+                        MB_OKCANCEL
+                    }
+                    None => MB_OK,
+                }
+            }
+            // Only a Cancel or Close button.
+            None => {
+                button_map.push((IDOK, cancel_id.unwrap_or(IDCANCEL)));
+                MB_OK
+            }
+        }
+    };
+    if let Some(cancel_id) = cancel_id {
+        if cancel_id != IDCANCEL {
+            button_map.push((IDCANCEL, cancel_id));
+        }
+    }
+
+    let fallback = MessageBoxFallback {
+        text: String::new(),
+        caption: String::new(),
+        style,
+        button_map,
+        warnings: vec![],
+    };
+    let ids = button_ids(style);
+    let shown: Vec<i32> = ids.iter().map(|id| fallback.button_id(*id)).collect();
+    let mut warnings: Vec<String> = buttons
+        .iter()
+        .filter(|(id, _)| !shown.contains(id))
+        .map(|(_, text)| format!("The button \"{}\" is not shown.", text))
+        .collect();
+    for (id, shown_id) in ids.iter().zip(shown.iter()) {
+        if let Some((_, text)) = buttons
+            .iter()
+            .find(|(button_id, _)| button_id == shown_id && !MESSAGE_BOX_IDS.contains(button_id))
+        {
+            let name = if *id == IDOK { "OK" } else { "Cancel" };
+            warnings.push(format!("The button \"{}\" is shown as {}.", text, name));
+        }
+    }
+    if !conf.radio_buttons.is_empty() {
+        warnings.push("The radio buttons are not shown.".to_string());
+    }
+    if !conf.verification_text.is_empty() {
+        warnings.push("The verification checkbox is not shown.".to_string());
+    }
+    if conf.expander.is_some() || !conf.expanded_information.is_empty() {
+        warnings.push("The expanded information is not shown.".to_string());
+    }
+    if !conf.footer.is_empty() {
+        warnings.push("The footer is not shown.".to_string());
+    }
+    if conf.custom_main_icon.is_some() && !is_question_icon(conf) {
+        warnings.push("The custom main icon is not shown.".to_string());
+    }
+
+    let mut style = style;
+    if let Some(default) = ids
+        .iter()
+        .position(|id| fallback.button_id(*id) == default_id)
+    {
+        style |= MessageBoxStyle((default as u32) << 8);
+    }
+    if is_question_icon(conf) {
+        style |= MB_ICONQUESTION;
+    } else if conf.main_icon == TD_ERROR_ICON {
+        style |= MB_ICONERROR;
+    } else if conf.main_icon == TD_WARNING_ICON || conf.main_icon == TD_SHIELD_ICON {
+        style |= MB_ICONWARNING;
+    } else if conf.main_icon == TD_INFORMATION_ICON {
+        style |= MB_ICONINFORMATION;
+    }
+    if conf.flags & TDF_RTL_LAYOUT == TDF_RTL_LAYOUT {
+        style |= MB_RTLREADING | MB_RIGHT;
+    }
+
+    MessageBoxFallback {
+        text,
+        caption: conf.window_title.clone(),
+        style,
+        warnings,
+        ..fallback
+    }
+}
+
+/// The question icon of `message_box_config`.
+fn is_question_icon(conf: &TaskDialogConfig) -> bool {
+    conf.custom_main_icon
+        .as_ref()
+        .and_then(|icon| icon.stock())
+        .is_some_and(|icon| icon.0 as usize == IDI_QUESTION)
+}
+
+/** Show the dialog with `MessageBoxW`, see `message_box_fallback`. */
+#[cfg(windows)]
+pub(crate) fn show_message_box_fallback(
+    conf: &mut TaskDialogConfig,
+) -> Result<TaskDialogResult, std::io::Error> {
+    use widestring::U16CString;
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MESSAGEBOX_STYLE};
+
+    let fallback = message_box_fallback(conf);
+    let text = U16CString::from_str_truncate(&fallback.text);
+    let caption = U16CString::from_str_truncate(&fallback.caption);
+    let ret = unsafe {
+        MessageBoxW(
+            Some(conf.parent),
+            PCWSTR(text.as_ptr()),
+            PCWSTR(caption.as_ptr()),
+            MESSAGEBOX_STYLE(fallback.style.0),
+        )
+    };
+    conf.is_destroyed = true;
+    if ret.0 == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    let mut button_id = fallback.button_id(ret.0);
+    if let Some(id) = crate::close_with(&conf.buttons, button_id) {
+        button_id = id;
+    }
+    let radio_button_id = match conf.radio_buttons.iter().find(|btn| btn.is_default) {
+        Some(btn) => btn.id,
+        None if conf.default_radio_buttons != 0 => conf.default_radio_buttons,
+        None if conf.flags & TDF_NO_DEFAULT_RADIO_BUTTON == TDF_NO_DEFAULT_RADIO_BUTTON => 0,
+        None => conf.radio_buttons.first().map_or(0, |btn| btn.id),
+    };
+    Ok(TaskDialogResult {
+        button_id,
+        radio_button_id,
+        checked: conf.flags & TDF_VERIFICATION_FLAG_CHECKED == TDF_VERIFICATION_FLAG_CHECKED,
+        expanded: conf.is_expanded,
+        renderer: DialogRenderer::MessageBox,
+        fallback_warnings: fallback.warnings,
+    })
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn topmost_callback(
+    hwnd: HWND,
+    msg: TASKDIALOG_NOTIFICATIONS,
+    _w_param: WPARAM,
+    _l_param: LPARAM,
+    _ref_data: *mut TaskDialogConfig,
+) -> HRESULT {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SetWindowPos, HWND_TOPMOST, SWP_NOMOVE, SWP_NOSIZE,
+    };
+
+    if msg == TDN_CREATED {
+        SetWindowPos(
+            hwnd,
+            Some(HWND_TOPMOST),
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE,
+        )
+        .ok();
+    }
+    S_OK
+}
+
+#[cfg(not(windows))]
+unsafe extern "system" fn topmost_callback(
+    _hwnd: HWND,
+    _msg: TASKDIALOG_NOTIFICATIONS,
+    _w_param: WPARAM,
+    _l_param: LPARAM,
+    _ref_data: *mut TaskDialogConfig,
+) -> HRESULT {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(style: MessageBoxStyle) -> TaskDialogConfig {
+        message_box_config(HWND::default(), "Text", "Caption", style)
+    }
+
+    fn custom_button_ids(conf: &TaskDialogConfig) -> Vec<i32> {
+        conf.buttons.iter().map(|btn| btn.id).collect()
+    }
+
+    fn cancellable(conf: &TaskDialogConfig) -> bool {
+        conf.flags & TDF_ALLOW_DIALOG_CANCELLATION == TDF_ALLOW_DIALOG_CANCELLATION
+    }
+
+    #[test]
+    fn common_button_sets() {
+        for (style, common_buttons) in [
+            (MB_OK, TDCBF_OK_BUTTON),
+            (MB_OKCANCEL, TDCBF_OK_BUTTON | TDCBF_CANCEL_BUTTON),
+            (
+                MB_YESNOCANCEL,
+                TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON,
+            ),
+            (MB_YESNO, TDCBF_YES_BUTTON | TDCBF_NO_BUTTON),
+            (MB_RETRYCANCEL, TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON),
+        ] {
+            let conf = config(style);
+            assert_eq!(conf.common_buttons, common_buttons, "{:?}", style);
+            assert!(conf.buttons.is_empty(), "{:?}", style);
+        }
+        // Escape closes a message box with only an OK button.
+        assert!(cancellable(&config(MB_OK)));
+        assert!(!cancellable(&config(MB_YESNO)));
+    }
+
+    #[test]
+    fn custom_button_sets_keep_the_message_box_order() {
+        let none = TASKDIALOG_COMMON_BUTTON_FLAGS::default();
+
+        let conf = config(MB_ABORTRETRYIGNORE);
+        assert_eq!(conf.common_buttons, none);
+        assert_eq!(custom_button_ids(&conf), vec![IDABORT, IDRETRY, IDIGNORE]);
+        assert!(!cancellable(&conf));
+
+        let conf = config(MB_CANCELTRYCONTINUE);
+        assert_eq!(conf.common_buttons, none);
+        assert_eq!(
+            custom_button_ids(&conf),
+            vec![IDCANCEL, IDTRYAGAIN, IDCONTINUE]
+        );
+        assert!(cancellable(&conf));
+    }
+
+    #[test]
+    fn default_buttons() {
+        for (style, default_button) in [
+            (MB_YESNOCANCEL, IDYES),
+            (MB_YESNOCANCEL | MB_DEFBUTTON1, IDYES),
+            (MB_YESNOCANCEL | MB_DEFBUTTON2, IDNO),
+            (MB_YESNOCANCEL | MB_DEFBUTTON3, IDCANCEL),
+            (MB_ABORTRETRYIGNORE | MB_DEFBUTTON3, IDIGNORE),
+            (MB_CANCELTRYCONTINUE | MB_DEFBUTTON2, IDTRYAGAIN),
+            // Beyond the last button
+            (MB_OKCANCEL | MB_DEFBUTTON3, IDOK),
+            (MB_YESNO | MB_DEFBUTTON4, IDYES),
+            (MB_OK | MB_DEFBUTTON2, IDOK),
+        ] {
+            assert_eq!(config(style).default_button, default_button, "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn icons() {
+        for (style, icon) in [
+            (MB_ICONERROR, TD_ERROR_ICON),
+            (MB_ICONSTOP, TD_ERROR_ICON),
+            (MB_ICONWARNING, TD_WARNING_ICON),
+            (MB_ICONINFORMATION, TD_INFORMATION_ICON),
+        ] {
+            let conf = config(style);
+            assert_eq!(conf.main_icon, icon, "{:?}", style);
+            assert!(conf.custom_main_icon.is_none(), "{:?}", style);
+        }
+
+        let conf = config(MB_ICONQUESTION);
+        assert!(conf.main_icon.is_null());
+        assert!(is_question_icon(&conf));
+
+        let conf = config(MB_OK);
+        assert!(conf.main_icon.is_null());
+        assert!(conf.custom_main_icon.is_none());
+    }
+
+    #[test]
+    fn rtl_reading() {
+        let rtl = |conf: &TaskDialogConfig| conf.flags & TDF_RTL_LAYOUT == TDF_RTL_LAYOUT;
+        assert!(rtl(&config(MB_OK | MB_RTLREADING)));
+        assert!(!rtl(&config(MB_OK)));
+    }
+
+    #[test]
+    fn topmost_installs_a_callback() {
+        assert!(config(MB_OK | MB_TOPMOST).callback.is_some());
+        assert!(config(MB_OK | MB_SYSTEMMODAL).callback.is_some());
+        assert!(config(MB_OK | MB_TASKMODAL | MB_SETFOREGROUND)
+            .callback
+            .is_none());
+    }
+
+    #[test]
+    fn text_and_caption() {
+        let conf = message_box_config(HWND(0x10 as _), "Save the changes?", "Editor", MB_OK);
+        assert_eq!(conf.parent, HWND(0x10 as _));
+        assert_eq!(conf.window_title, "Editor");
+        assert_eq!(conf.content, "Save the changes?");
+        assert!(conf.main_instruction.is_empty());
+    }
+
+    fn fallback_config(common_buttons: TASKDIALOG_COMMON_BUTTON_FLAGS) -> TaskDialogConfig {
+        TaskDialogConfig {
+            main_instruction: "Save the changes?".to_string(),
+            content: "The document has unsaved changes.".to_string(),
+            common_buttons,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fallback_common_buttons() {
+        let fallback = message_box_fallback(&fallback_config(
+            TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON,
+        ));
+        assert_eq!(fallback.style, MB_YESNOCANCEL);
+        assert_eq!(
+            fallback.text,
+            "Save the changes?\n\nThe document has unsaved changes."
+        );
+        assert!(fallback.warnings.is_empty());
+        for id in [IDYES, IDNO, IDCANCEL] {
+            assert_eq!(fallback.button_id(id), id);
+        }
+
+        let fallback = message_box_fallback(&fallback_config(TDCBF_YES_BUTTON | TDCBF_NO_BUTTON));
+        assert_eq!(fallback.style, MB_YESNO);
+        let fallback =
+            message_box_fallback(&fallback_config(TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON));
+        assert_eq!(fallback.style, MB_RETRYCANCEL);
+        let fallback = message_box_fallback(&fallback_config(TDCBF_OK_BUTTON));
+        assert_eq!(fallback.style, MB_OK);
+    }
+
+    #[test]
+    fn fallback_close_is_shown_as_cancel() {
+        let fallback = message_box_fallback(&fallback_config(
+            TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CLOSE_BUTTON,
+        ));
+        assert_eq!(fallback.style, MB_YESNOCANCEL);
+        assert_eq!(fallback.button_id(IDCANCEL), IDCLOSE);
+        assert!(fallback.warnings.is_empty());
+
+        let fallback = message_box_fallback(&fallback_config(TDCBF_OK_BUTTON | TDCBF_CLOSE_BUTTON));
+        assert_eq!(fallback.style, MB_OKCANCEL);
+        assert_eq!(fallback.button_id(IDOK), IDOK);
+        assert_eq!(fallback.button_id(IDCANCEL), IDCLOSE);
+
+        // Only a Close button
+        let fallback = message_box_fallback(&fallback_config(TDCBF_CLOSE_BUTTON));
+        assert_eq!(fallback.style, MB_OK);
+        assert_eq!(fallback.button_id(IDOK), IDCLOSE);
+    }
+
+    #[test]
+    fn fallback_collapses_custom_buttons() {
+        let mut conf = fallback_config(TDCBF_CANCEL_BUTTON);
+        conf.buttons = vec![
+            TaskDialogButton::new(100, "Save"),
+            TaskDialogButton::new(101, "Discard"),
+        ];
+        let fallback = message_box_fallback(&conf);
+        assert_eq!(fallback.style, MB_OKCANCEL);
+        assert_eq!(fallback.button_id(IDOK), 100);
+        assert_eq!(fallback.button_id(IDCANCEL), IDCANCEL);
+        assert_eq!(
+            fallback.warnings,
+            vec![
+                "The button \"Discard\" is not shown.".to_string(),
+                "The button \"Save\" is shown as OK.".to_string(),
+            ]
+        );
+
+        // Without a way to cancel, Cancel reports the next button.
+        conf.common_buttons = TASKDIALOG_COMMON_BUTTON_FLAGS::default();
+        conf.buttons.push(TaskDialogButton::new(102, "Later"));
+        let fallback = message_box_fallback(&conf);
+        assert_eq!(fallback.style, MB_OKCANCEL);
+        assert_eq!(fallback.button_id(IDOK), 100);
+        assert_eq!(fallback.button_id(IDCANCEL), 101);
+        assert_eq!(
+            fallback.warnings,
+            vec![
+                "The button \"Later\" is not shown.".to_string(),
+                "The button \"Save\" is shown as OK.".to_string(),
+                "The button \"Discard\" is shown as Cancel.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn fallback_warns_about_the_other_elements() {
+        let conf = TaskDialogConfig {
+            radio_buttons: vec![TaskDialogButton::new(10, "Option")],
+            verification_text: "Don't ask again".to_string(),
+            expander: Some(Expander {
+                text: "Details".to_string(),
+                ..Default::default()
+            }),
+            footer: "Footer".to_string(),
+            custom_main_icon: Some(DialogIcon::from_stock(TD_SHIELD_ICON)),
+            ..fallback_config(TDCBF_OK_BUTTON)
+        };
+        assert_eq!(
+            message_box_fallback(&conf).warnings,
+            vec![
+                "The radio buttons are not shown.".to_string(),
+                "The verification checkbox is not shown.".to_string(),
+                "The expanded information is not shown.".to_string(),
+                "The footer is not shown.".to_string(),
+                "The custom main icon is not shown.".to_string(),
+            ]
+        );
+
+        let conf = TaskDialogConfig {
+            expanded_information: "Details".to_string(),
+            ..fallback_config(TDCBF_OK_BUTTON)
+        };
+        assert_eq!(
+            message_box_fallback(&conf).warnings,
+            vec!["The expanded information is not shown.".to_string()]
+        );
+    }
+
+    #[test]
+    fn fallback_default_button() {
+        let mut conf = fallback_config(TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON);
+        conf.default_button = IDNO;
+        assert_eq!(
+            message_box_fallback(&conf).style,
+            MB_YESNOCANCEL | MB_DEFBUTTON2
+        );
+        conf.default_button = IDCANCEL;
+        assert_eq!(
+            message_box_fallback(&conf).style,
+            MB_YESNOCANCEL | MB_DEFBUTTON3
+        );
+
+        // A default custom button is collapsed into OK.
+        let mut conf = fallback_config(TDCBF_CANCEL_BUTTON);
+        conf.buttons = vec![
+            TaskDialogButton::new(100, "Save"),
+            TaskDialogButton {
+                is_default: true,
+                ..TaskDialogButton::new(101, "Discard")
+            },
+        ];
+        let fallback = message_box_fallback(&conf);
+        assert_eq!(fallback.style, MB_OKCANCEL | MB_DEFBUTTON1);
+        assert_eq!(fallback.button_id(IDOK), 101);
+    }
+
+    #[test]
+    fn fallback_icons() {
+        for (icon, style) in [
+            (TD_ERROR_ICON, MB_ICONERROR),
+            (TD_WARNING_ICON, MB_ICONWARNING),
+            (TD_SHIELD_ICON, MB_ICONWARNING),
+            (TD_INFORMATION_ICON, MB_ICONINFORMATION),
+        ] {
+            let conf = TaskDialogConfig {
+                main_icon: icon,
+                ..fallback_config(TDCBF_OK_BUTTON)
+            };
+            assert_eq!(message_box_fallback(&conf).style, MB_OK | style);
+        }
+
+        let conf = config(MB_YESNO | MB_ICONQUESTION);
+        let fallback = message_box_fallback(&conf);
+        assert_eq!(fallback.style, MB_YESNO | MB_ICONQUESTION);
+        assert!(fallback.warnings.is_empty());
+    }
+
+    #[test]
+    fn fallback_rtl_layout() {
+        let conf = TaskDialogConfig {
+            flags: TDF_RTL_LAYOUT,
+            ..fallback_config(TDCBF_OK_BUTTON)
+        };
+        assert_eq!(
+            message_box_fallback(&conf).style,
+            MB_OK | MB_RTLREADING | MB_RIGHT
+        );
+    }
+
+    #[test]
+    fn fallback_round_trip() {
+        let style = MB_YESNOCANCEL | MB_ICONWARNING | MB_DEFBUTTON2 | MB_RTLREADING;
+        let fallback = message_box_fallback(&config(style));
+        assert_eq!(fallback.style, style | MB_RIGHT);
+        assert_eq!(fallback.text, "Text");
+        assert_eq!(fallback.caption, "Caption");
+    }
+
+    #[test]
+    fn fallback_strips_hyperlinks() {
+        let conf = TaskDialogConfig {
+            flags: TDF_ENABLE_HYPERLINKS,
+            content: "See <a href=\"https://example.com\">the help</a> for 1 < 2.".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(message_box_fallback(&conf).text, "See the help for 1 < 2.");
+    }
+}
@@ -0,0 +1,140 @@
+use crate::*;
+use std::{
+    backtrace::Backtrace,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+const COPY_BUTTON_ID: i32 = 100;
+const SAVE_BUTTON_ID: i32 = 101;
+
+/// Set while the panic dialog is shown, a panic of the dialog itself
+/// must not show another dialog.
+static IN_PANIC_DIALOG: AtomicBool = AtomicBool::new(false);
+
+/// The options of `install_panic_dialog`.
+pub struct PanicDialogOptions {
+    /// Uses the window title of `set_dialog_defaults` when empty.
+    pub window_title: String,
+    pub main_instruction: String,
+    /// Show a "Copy details" button which copies the report to the clipboard.
+    pub copy_button: bool,
+    /// Show a "Save report…" button which saves the report to a file.
+    pub save_button: bool,
+    /// Show a link to the issue tracker in the footer, no link when empty.
+    /// `"`, `<` and `>` in the URL are percent-encoded.
+    pub issue_tracker_url: String,
+}
+
+impl Default for PanicDialogOptions {
+    fn default() -> Self {
+        PanicDialogOptions {
+            window_title: "".to_string(),
+            main_instruction: "The application has encountered an unexpected error".to_string(),
+            copy_button: true,
+            save_button: true,
+            issue_tracker_url: "".to_string(),
+        }
+    }
+}
+
+/// Install a panic hook which shows an error dialog with the panic message,
+/// the location and the backtrace. The previously installed hook is still
+/// called before the dialog is shown.
+pub fn install_panic_dialog(options: PanicDialogOptions) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+
+        if IN_PANIC_DIALOG.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let payload = info.payload();
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+        let mut details = String::new();
+        if let Some(name) = std::thread::current().name() {
+            details.push_str(&format!("Thread: {}\n", name));
+        }
+        if let Some(location) = info.location() {
+            details.push_str(&format!("Location: {}\n", location));
+        }
+        details.push_str(&format!("\nBacktrace:\n{}", Backtrace::force_capture()));
+
+        let mut buttons = vec![];
+        if options.copy_button {
+            buttons.push(TaskDialogButton::new(COPY_BUTTON_ID, "Copy details"));
+        }
+        if options.save_button {
+            buttons.push(TaskDialogButton::new(SAVE_BUTTON_ID, "Save report…"));
+        }
+
+        let mut conf = TaskDialogConfig::from_defaults();
+        if !options.window_title.is_empty() {
+            conf.window_title = options.window_title.clone();
+        }
+        if !options.issue_tracker_url.is_empty() {
+            conf.flags |= TDF_ENABLE_HYPERLINKS;
+            // The characters which would end the attribute or the tag are percent-encoded
+            let url = options
+                .issue_tracker_url
+                .replace('"', "%22")
+                .replace('<', "%3C")
+                .replace('>', "%3E");
+            conf.footer = format!("<a href=\"{}\">Report this issue</a>", url);
+            conf.hyperlink_callback = Some(open_hyperlink);
+        }
+        conf.common_buttons = TDCBF_CLOSE_BUTTON;
+        conf.main_icon = TD_ERROR_ICON;
+        conf.main_instruction = options.main_instruction.clone();
+        conf.content = message;
+        conf.expander = Some(Expander {
+            text: details,
+            ..Default::default()
+        });
+        conf.buttons = buttons;
+        #[cfg(windows)]
+        {
+            conf.callback = Some(panic_dialog_callback);
+        }
+        show_task_dialog(&mut conf).ok();
+
+        IN_PANIC_DIALOG.store(false, Ordering::SeqCst);
+    }));
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn panic_dialog_callback(
+    hwnd: HWND,
+    msg: TASKDIALOG_NOTIFICATIONS,
+    w_param: WPARAM,
+    _l_param: LPARAM,
+    ref_data: *mut TaskDialogConfig,
+) -> HRESULT {
+    if msg == TDN_BUTTON_CLICKED {
+        let conf = &*ref_data;
+        let report = || match &conf.expander {
+            Some(expander) => format!("{}\n\n{}", conf.content, expander.text),
+            None => conf.content.clone(),
+        };
+        match w_param.0 as i32 {
+            COPY_BUTTON_ID => {
+                crate::shell::copy_to_clipboard(hwnd, &report()).ok();
+                return S_FALSE;
+            }
+            SAVE_BUTTON_ID => {
+                if let Some(path) = crate::shell::save_file_dialog(hwnd, "panic-report.txt") {
+                    std::fs::write(path, report()).ok();
+                }
+                return S_FALSE;
+            }
+            _ => {}
+        }
+    }
+    S_OK
+}
@@ -0,0 +1,254 @@
+use crate::*;
+use std::{io::Error, sync::Mutex};
+
+/// App-wide defaults used by the preset dialogs and `TaskDialogConfig::from_defaults`.
+pub struct DialogDefaults {
+    /// The owner window of the dialogs.
+    pub parent: HWND,
+    /// Used when a preset is called with an empty title.
+    pub window_title: String,
+    pub flags: TASKDIALOG_FLAGS,
+    /// Set `TaskDialogConfig::activation_context`.
+    pub activation_context: bool,
+}
+
+// HWND is only passed to the dialog as its owner window.
+unsafe impl Send for DialogDefaults {}
+
+impl Default for DialogDefaults {
+    fn default() -> Self {
+        DialogDefaults {
+            parent: HWND::default(),
+            window_title: "".to_string(),
+            flags: TASKDIALOG_FLAGS::default(),
+            activation_context: false,
+        }
+    }
+}
+
+static DEFAULTS: Mutex<Option<DialogDefaults>> = Mutex::new(None);
+
+/// Set the app-wide defaults of the dialogs.
+pub fn set_dialog_defaults(defaults: DialogDefaults) {
+    *DEFAULTS.lock().unwrap_or_else(|e| e.into_inner()) = Some(defaults);
+}
+
+impl TaskDialogConfig {
+    /// Create a config with the parent, window title, flags and activation
+    /// context option of `set_dialog_defaults`.
+    pub fn from_defaults() -> Self {
+        let defaults = DEFAULTS.lock().unwrap_or_else(|e| e.into_inner());
+        match defaults.as_ref() {
+            Some(defaults) => TaskDialogConfig {
+                parent: defaults.parent,
+                window_title: defaults.window_title.clone(),
+                flags: defaults.flags,
+                activation_context: defaults.activation_context,
+                ..Default::default()
+            },
+            None => TaskDialogConfig::default(),
+        }
+    }
+}
+
+/// The answer of `ask_yes_no_cancel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YesNoCancel {
+    Yes,
+    No,
+    Cancel,
+}
+
+fn preset(title: &str, main_instruction: &str, content: &str) -> TaskDialogConfig {
+    let mut conf = TaskDialogConfig::from_defaults();
+    if !title.is_empty() {
+        conf.window_title = title.to_string();
+    }
+    conf.main_instruction = main_instruction.to_string();
+    conf.content = content.to_string();
+    conf
+}
+
+/// Format the `source()` chain of the error, one cause per line.
+pub(crate) fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut chain = String::new();
+    let mut source = err.source();
+    while let Some(err) = source {
+        if !chain.is_empty() {
+            chain.push('\n');
+        }
+        chain.push_str(&format!("Caused by: {}", err));
+        source = err.source();
+    }
+    chain
+}
+
+/** Ask a question with Yes and No buttons, return `true` on Yes. */
+pub fn confirm(title: &str, question: &str) -> bool {
+    let mut conf = TaskDialogConfig {
+        common_buttons: TDCBF_YES_BUTTON | TDCBF_NO_BUTTON,
+        ..preset(title, question, "")
+    };
+    matches!(show_task_dialog(&mut conf), Ok(result) if result.button_id == IDYES)
+}
+
+/** Ask a question with Yes, No and Cancel buttons. */
+pub fn ask_yes_no_cancel(title: &str, question: &str) -> YesNoCancel {
+    let mut conf = TaskDialogConfig {
+        common_buttons: TDCBF_YES_BUTTON | TDCBF_NO_BUTTON | TDCBF_CANCEL_BUTTON,
+        ..preset(title, question, "")
+    };
+    match show_task_dialog(&mut conf) {
+        Ok(result) if result.button_id == IDYES => YesNoCancel::Yes,
+        Ok(result) if result.button_id == IDNO => YesNoCancel::No,
+        _ => YesNoCancel::Cancel,
+    }
+}
+
+/** Show a warning with Retry and Cancel buttons, return `true` on Retry. */
+pub fn ask_retry_cancel(title: &str, main_instruction: &str, content: &str) -> bool {
+    let mut conf = TaskDialogConfig {
+        common_buttons: TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON,
+        main_icon: TD_WARNING_ICON,
+        ..preset(title, main_instruction, content)
+    };
+    matches!(show_task_dialog(&mut conf), Ok(result) if result.button_id == IDRETRY)
+}
+
+/** Show a warning, the dialog have only the OK button */
+pub fn warn(title: &str, main_instruction: &str, content: &str) -> Option<Error> {
+    let mut conf = TaskDialogConfig {
+        common_buttons: TDCBF_OK_BUTTON,
+        main_icon: TD_WARNING_ICON,
+        ..preset(title, main_instruction, content)
+    };
+    show_task_dialog(&mut conf).err()
+}
+
+/** Show an information, the dialog have only the OK button */
+pub fn info(title: &str, main_instruction: &str, content: &str) -> Option<Error> {
+    let mut conf = TaskDialogConfig {
+        common_buttons: TDCBF_OK_BUTTON,
+        main_icon: TD_INFORMATION_ICON,
+        ..preset(title, main_instruction, content)
+    };
+    show_task_dialog(&mut conf).err()
+}
+
+/// An error dialog with the `source()` chain of the error in the expandable area.
+pub(crate) fn error_config(title: &str, err: &dyn std::error::Error) -> TaskDialogConfig {
+    let chain = error_chain(err);
+    TaskDialogConfig {
+        common_buttons: TDCBF_OK_BUTTON,
+        main_icon: TD_ERROR_ICON,
+        expander: if chain.is_empty() {
+            None
+        } else {
+            Some(Expander {
+                text: chain,
+                ..Default::default()
+            })
+        },
+        ..preset(title, &err.to_string(), "")
+    }
+}
+
+/** Show an error with its `source()` chain in the expandable area. */
+pub fn error_details(err: &dyn std::error::Error) -> Option<Error> {
+    show_task_dialog(&mut error_config("", err)).err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Chained {
+        message: &'static str,
+        source: Option<Box<Chained>>,
+    }
+
+    impl fmt::Display for Chained {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl std::error::Error for Chained {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source
+                .as_ref()
+                .map(|source| &**source as &(dyn std::error::Error + 'static))
+        }
+    }
+
+    fn chained(messages: &[&'static str]) -> Chained {
+        let (message, sources) = messages.split_first().unwrap();
+        Chained {
+            message,
+            source: if sources.is_empty() {
+                None
+            } else {
+                Some(Box::new(chained(sources)))
+            },
+        }
+    }
+
+    #[test]
+    fn error_chain_lists_the_sources() {
+        let err = chained(&[
+            "The settings could not be saved",
+            "The file is read-only",
+            "Access is denied",
+        ]);
+        assert_eq!(
+            error_chain(&err),
+            "Caused by: The file is read-only\nCaused by: Access is denied"
+        );
+        assert_eq!(error_chain(&chained(&["No source"])), "");
+    }
+
+    #[test]
+    fn error_config_shows_the_chain_in_the_expander() {
+        let err = chained(&["The settings could not be saved", "Access is denied"]);
+        let conf = error_config("Settings", &err);
+        assert_eq!(conf.window_title, "Settings");
+        assert_eq!(conf.main_instruction, "The settings could not be saved");
+        assert_eq!(conf.main_icon, TD_ERROR_ICON);
+        assert_eq!(
+            conf.expander.map(|expander| expander.text),
+            Some("Caused by: Access is denied".to_string())
+        );
+
+        let conf = error_config("Settings", &chained(&["No source"]));
+        assert!(conf.expander.is_none());
+    }
+
+    #[test]
+    fn configs_inherit_the_dialog_defaults() {
+        set_dialog_defaults(DialogDefaults {
+            parent: HWND(0x1234 as _),
+            window_title: "Editor".to_string(),
+            flags: TDF_CAN_BE_MINIMIZED,
+            activation_context: true,
+        });
+        let conf = TaskDialogConfig::from_defaults();
+        let titled = preset("Save", "Save the changes?", "");
+        let untitled = preset("", "Save the changes?", "");
+        set_dialog_defaults(DialogDefaults::default());
+
+        assert_eq!(conf.parent, HWND(0x1234 as _));
+        assert_eq!(conf.window_title, "Editor");
+        assert_eq!(conf.flags, TDF_CAN_BE_MINIMIZED);
+        assert!(conf.activation_context);
+        assert_eq!(titled.window_title, "Save");
+        assert_eq!(untitled.window_title, "Editor");
+        assert_eq!(untitled.parent, HWND(0x1234 as _));
+        assert!(untitled.activation_context);
+
+        let conf = TaskDialogConfig::from_defaults();
+        assert_eq!(conf.parent, HWND::default());
+        assert!(!conf.activation_context);
+    }
+}
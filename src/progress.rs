@@ -0,0 +1,436 @@
+use crate::*;
+use std::{
+    io::Error,
+    iter::FromIterator,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, TryRecvError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The minimum time between two updates of the content text.
+const MESSAGE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Set when the user cancels the progress dialog, the work polls it and
+/// stops early.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancel the work, the progress dialog reports `Cancelled`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+/// The outcome of a progress dialog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressOutcome<T> {
+    /// The work finished without being cancelled.
+    Completed(T),
+    /// The user cancelled the dialog, or the work cancelled the token.
+    Cancelled,
+}
+
+/// Where `Progress::set_status` shows the status text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusText {
+    Content,
+    /// The dialog needs a footer when it is created, e.g. a space.
+    Footer,
+}
+
+/// The progress of the work, shown by the progress dialog.
+pub struct Progress {
+    handle: TaskDialogHandle,
+    cancel: CancellationToken,
+    length: Option<u64>,
+    position: u64,
+    /// The mode and the percentage shown by the dialog.
+    shown_marquee: Option<bool>,
+    shown_percentage: Option<usize>,
+    message: String,
+    shown_message: String,
+    message_updated: Option<Instant>,
+    status: Option<(ProgressFormatter, StatusText)>,
+}
+
+impl Progress {
+    fn new(handle: TaskDialogHandle, cancel: CancellationToken, length: Option<u64>) -> Self {
+        Progress {
+            handle,
+            cancel,
+            length,
+            position: 0,
+            shown_marquee: None,
+            shown_percentage: None,
+            message: "".to_string(),
+            shown_message: "".to_string(),
+            message_updated: None,
+            status: None,
+        }
+    }
+
+    /// The handle of the dialog, to update the other texts.
+    pub fn handle(&self) -> &TaskDialogHandle {
+        &self.handle
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Set the number of steps, the bar is a marquee when it is `None`.
+    pub fn set_length(&mut self, length: Option<u64>) {
+        self.length = length;
+        self.update_bar();
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+        self.update_bar();
+    }
+
+    /// Advance the position by `delta` steps.
+    pub fn inc(&mut self, delta: u64) {
+        self.set_position(self.position.saturating_add(delta));
+    }
+
+    /// Set the content text, at most 20 times per second, the text set in
+    /// between is shown with the next update. Not shown while a status is
+    /// shown as the content.
+    pub fn set_message(&mut self, message: &str) {
+        if self.message != message {
+            self.message = message.to_string();
+        }
+        self.update_message();
+    }
+
+    /// Show the text of the formatter, updated with the position and the
+    /// length. A status shown as the content replaces `set_message`.
+    pub fn set_status(&mut self, formatter: ProgressFormatter, text: StatusText) {
+        self.status = Some((formatter, text));
+        self.update_bar();
+    }
+
+    fn update_bar(&mut self) {
+        let marquee = self.length.is_none();
+        if self.shown_marquee != Some(marquee) {
+            self.shown_marquee = Some(marquee);
+            self.shown_percentage = None;
+            self.handle.set_marquee_process_bar(marquee);
+            self.handle.set_process_bar_marquee(marquee, 0);
+        }
+        if let Some(length) = self.length {
+            let percentage = match length {
+                0 => 100,
+                length => (self.position.min(length) as u128 * 100 / length as u128) as usize,
+            };
+            if self.shown_percentage != Some(percentage) {
+                self.shown_percentage = Some(percentage);
+                self.handle.set_process_bar(percentage);
+            }
+        }
+        if let Some((formatter, text)) = self.status.as_mut() {
+            if let Some(status) = formatter.update(self.position, self.length) {
+                match text {
+                    StatusText::Content => self.handle.set_content(&status),
+                    StatusText::Footer => self.handle.set_footer(&status),
+                }
+            }
+        }
+        self.update_message();
+    }
+
+    fn update_message(&mut self) {
+        // The status owns the content
+        if matches!(self.status, Some((_, StatusText::Content))) {
+            return;
+        }
+        if self.shown_message == self.message {
+            return;
+        }
+        let now = Instant::now();
+        if self
+            .message_updated
+            .is_some_and(|updated| now.duration_since(updated) < MESSAGE_INTERVAL)
+        {
+            return;
+        }
+        self.message_updated = Some(now);
+        self.shown_message = self.message.clone();
+        self.handle.set_content(&self.message);
+    }
+}
+
+/// The dialog of `ProgressDialog::run` and `ProgressDialogExt::progress_dialog`.
+struct Running {
+    dialog: Option<RunningDialog>,
+    progress: Progress,
+}
+
+impl Running {
+    /** Show the dialog on its own thread, and wait until it is created.
+    The dialog gets a progress bar and a Cancel button when it has no
+    buttons. Clicking a button or closing the dialog cancels the token.
+
+    A message box cannot show the progress, the `MessageBoxW` fallback is
+    disabled and the dialog fails without comctl32 version 6, see
+    `TaskDialogConfig::activation_context`. */
+    fn start(mut conf: TaskDialogConfig, length: Option<u64>) -> Result<Running, Error> {
+        conf.message_box_fallback = false;
+        let bars = TDF_SHOW_PROGRESS_BAR | TDF_SHOW_MARQUEE_PROGRESS_BAR;
+        if conf.flags & bars == Default::default() {
+            conf.flags |= TDF_SHOW_PROGRESS_BAR;
+        }
+        if conf.buttons.is_empty() && conf.common_buttons == Default::default() {
+            conf.common_buttons = TDCBF_CANCEL_BUTTON;
+        }
+
+        let cancel = CancellationToken::new();
+        let events = conf.events();
+        let (created_sender, created) = channel();
+        let relay_cancel = cancel.clone();
+        std::thread::Builder::new()
+            .name("task-dialog-progress".to_string())
+            .spawn(move || {
+                for event in events {
+                    match event {
+                        TaskDialogEvent::Created => {
+                            created_sender.send(()).ok();
+                        }
+                        TaskDialogEvent::ButtonClicked { vetoed: false, .. }
+                        | TaskDialogEvent::Destroyed => relay_cancel.cancel(),
+                        _ => {}
+                    }
+                }
+            })?;
+
+        let dialog = spawn_task_dialog(conf)?;
+        let handle = dialog.handle().clone();
+        // The dialog thread returned without creating the dialog when the
+        // events are disconnected, the work runs without a dialog. The owner
+        // window may belong to this thread, its messages are dispatched.
+        let is_created = wait_dispatching(|| match created.try_recv() {
+            Ok(()) => Some(true),
+            Err(TryRecvError::Disconnected) => Some(false),
+            Err(TryRecvError::Empty) => None,
+        });
+        let dialog = if is_created {
+            Some(dialog)
+        } else {
+            dialog.join()?;
+            None
+        };
+        let mut progress = Progress::new(handle, cancel, length);
+        progress.update_bar();
+        Ok(Running { dialog, progress })
+    }
+
+    /// Close the dialog with `outcome` and wait until it is closed,
+    /// dispatching the messages of the calling thread.
+    fn finish(&mut self, outcome: i32) -> Result<(), Error> {
+        match self.dialog.take() {
+            Some(dialog) => {
+                dialog.close(outcome);
+                dialog.join().map(|_| ())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/** A task dialog with a progress bar, which shows the progress of work
+running on the calling thread.
+
+```
+use win_task_dialog::*;
+
+let conf = TaskDialogConfig {
+    window_title: "Copying".to_string(),
+    ..Default::default()
+};
+let outcome = ProgressDialog::run(conf, |progress, cancel| {
+    progress.set_length(Some(3));
+    for file in ["a.txt", "b.txt", "c.txt"] {
+        if cancel.is_cancelled() {
+            break;
+        }
+        progress.set_message(file);
+        progress.inc(1);
+    }
+    progress.position()
+})
+.unwrap();
+assert_eq!(outcome, ProgressOutcome::Completed(3));
+```
+*/
+pub struct ProgressDialog;
+
+impl ProgressDialog {
+    /** Show the dialog, run `work` on the calling thread and close the
+    dialog when it returns. The bar is a marquee until `work` sets a length.
+
+    `conf.event_sender` is replaced, the events are used to cancel the
+    token. Returns an error when the dialog could not be shown.
+
+    The messages of the calling thread are dispatched while the dialog is
+    opened and closed, but not while `work` runs. When `conf.parent` belongs
+    to the calling thread, the owner window does not repaint or respond
+    until `work` returns, run long work with an owner of another thread or
+    pump the messages from `work`. */
+    pub fn run<T, F>(conf: TaskDialogConfig, work: F) -> Result<ProgressOutcome<T>, Error>
+    where
+        F: FnOnce(&mut Progress, &CancellationToken) -> T,
+    {
+        let mut running = Running::start(conf, None)?;
+        let cancel = running.progress.cancel.clone();
+        let value = work(&mut running.progress, &cancel);
+        let cancelled = cancel.is_cancelled();
+        running.finish(if cancelled { IDCANCEL } else { IDOK })?;
+        Ok(if cancelled {
+            ProgressOutcome::Cancelled
+        } else {
+            ProgressOutcome::Completed(value)
+        })
+    }
+}
+
+/// An iterator showing its progress in a task dialog, see
+/// `ProgressDialogExt::progress_dialog`.
+pub struct ProgressIter<I: Iterator> {
+    iter: I,
+    running: Running,
+    item_text: Option<fn(&I::Item) -> String>,
+    done: bool,
+}
+
+impl<I: Iterator> ProgressIter<I> {
+    /// Show the text of the current item as the content.
+    pub fn with_item_text(mut self, item_text: fn(&I::Item) -> String) -> Self {
+        self.item_text = Some(item_text);
+        self
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.running.progress.cancel.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.running.progress.is_cancelled()
+    }
+
+    /** Collect the remaining items and close the dialog, `Cancelled` when
+    the user cancelled before the last item. */
+    pub fn collect_outcome<B: FromIterator<I::Item>>(
+        mut self,
+    ) -> Result<ProgressOutcome<B>, Error> {
+        let items: B = self.by_ref().collect();
+        let cancelled = self.is_cancelled();
+        self.running
+            .finish(if cancelled { IDCANCEL } else { IDOK })?;
+        Ok(if cancelled {
+            ProgressOutcome::Cancelled
+        } else {
+            ProgressOutcome::Completed(items)
+        })
+    }
+}
+
+impl<I: Iterator> Iterator for ProgressIter<I> {
+    type Item = I::Item;
+
+    /// The next item, `None` once the dialog is cancelled.
+    fn next(&mut self) -> Option<I::Item> {
+        if self.done || self.is_cancelled() {
+            return None;
+        }
+        let progress = &mut self.running.progress;
+        match self.iter.next() {
+            Some(item) => {
+                if let Some(item_text) = self.item_text {
+                    progress.set_message(&item_text(&item));
+                }
+                progress.inc(1);
+                Some(item)
+            }
+            None => {
+                self.done = true;
+                let length = progress.position();
+                progress.set_length(Some(length));
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done || self.is_cancelled() {
+            (0, Some(0))
+        } else {
+            (0, self.iter.size_hint().1)
+        }
+    }
+}
+
+impl<I: Iterator> Drop for ProgressIter<I> {
+    fn drop(&mut self) {
+        let outcome = if self.done { IDOK } else { IDCANCEL };
+        self.running.finish(outcome).ok();
+    }
+}
+
+/// Adds `progress_dialog` to the iterators.
+pub trait ProgressDialogExt: Iterator + Sized {
+    /** Show a task dialog with the progress of the iteration, the position
+    is the number of items taken. The bar is a marquee when the length of
+    the iterator is unknown. The iteration stops when the user cancels.
+
+    ```
+    use win_task_dialog::*;
+
+    let files = vec!["a.txt", "b.txt", "c.txt"];
+    let outcome = files
+        .iter()
+        .progress_dialog(TaskDialogConfig::default())
+        .unwrap()
+        .with_item_text(|file| format!("Copying {}", file))
+        .map(|file| file.len())
+        .collect::<Vec<_>>();
+    assert_eq!(outcome, vec![5, 5, 5]);
+    ```
+    */
+    fn progress_dialog(self, conf: TaskDialogConfig) -> Result<ProgressIter<Self>, Error> {
+        let length = match self.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper as u64),
+            _ => None,
+        };
+        Ok(ProgressIter {
+            iter: self,
+            running: Running::start(conf, length)?,
+            item_text: None,
+            done: false,
+        })
+    }
+}
+
+impl<I: Iterator> ProgressDialogExt for I {}
@@ -0,0 +1,293 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The time source of `ProgressFormatter`, a fixed clock makes the
+/// estimates deterministic.
+pub trait Clock {
+    /// The time since an arbitrary origin, which never goes backwards.
+    fn now(&self) -> Duration;
+}
+
+/// The monotonic clock of the system.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A clock which only moves when it is told to, clones share the time.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/** Renders the status text of a progress dialog from a template, with the
+smoothed rate and the remaining time.
+
+The placeholders of the template are:
+
+- `{pos}` and `{len}`, the position and the length
+- `{percent}`, the percentage of the length
+- `{bytes}` and `{total_bytes}`, the position and the length as bytes
+- `{per_sec}` and `{bytes_per_sec}`, the rate as items or bytes per second
+- `{elapsed}` and `{eta}`, the time since the start and the remaining time
+
+An unknown value is rendered as `?`, the other text is kept as is.
+
+```
+use std::time::Duration;
+use win_task_dialog::*;
+
+let clock = ManualClock::new();
+let mut status = ProgressFormatter::with_clock(
+    "{pos}/{len} files · {bytes_per_sec} · {eta} remaining",
+    clock.clone(),
+);
+status.update(0, Some(100));
+clock.advance(Duration::from_secs(2));
+let text = status.update(20, Some(100)).unwrap();
+assert_eq!(text, "20/100 files · 10 B/s · 0:08 remaining");
+// Updates in between the refresh interval are not rendered.
+assert_eq!(status.update(21, Some(100)), None);
+```
+*/
+pub struct ProgressFormatter {
+    template: String,
+    clock: Box<dyn Clock + Send>,
+    smoothing: f64,
+    refresh_interval: Duration,
+    start: Duration,
+    position: u64,
+    length: Option<u64>,
+    /// The time and the position of the previous rate sample.
+    sample: Option<(Duration, u64)>,
+    /// The items per second.
+    rate: Option<f64>,
+    rendered: Option<Duration>,
+}
+
+impl ProgressFormatter {
+    /// A formatter with the system clock.
+    pub fn new(template: &str) -> Self {
+        ProgressFormatter::with_clock(template, SystemClock::default())
+    }
+
+    /// A formatter with the clock, the estimates start at the current time
+    /// of the clock.
+    pub fn with_clock<C: Clock + Send + 'static>(template: &str, clock: C) -> Self {
+        let start = clock.now();
+        ProgressFormatter {
+            template: template.to_string(),
+            clock: Box::new(clock),
+            smoothing: 0.3,
+            refresh_interval: Duration::from_millis(200),
+            start,
+            position: 0,
+            length: None,
+            sample: None,
+            rate: None,
+            rendered: None,
+        }
+    }
+
+    /// The weight of the newest sample in the smoothed rate, between 0 and 1.
+    /// 1 is the rate of the last refresh interval only, the default is 0.3.
+    pub fn set_smoothing(&mut self, smoothing: f64) {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+    }
+
+    /// The minimum time between two rendered texts, 200 milliseconds by default.
+    pub fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.refresh_interval = refresh_interval;
+    }
+
+    pub fn set_template(&mut self, template: &str) {
+        self.template = template.to_string();
+    }
+
+    /** Set the position and the length, and return the text when it is due:
+    on the first update, once the refresh interval has passed since the
+    previous text, and when the position reaches the length. The rate is
+    sampled at the same times. */
+    pub fn update(&mut self, position: u64, length: Option<u64>) -> Option<String> {
+        self.position = position;
+        self.length = length;
+        let now = self.clock.now();
+        let finished = length.is_some_and(|length| position >= length);
+        let due = match self.rendered {
+            Some(rendered) => now.saturating_sub(rendered) >= self.refresh_interval,
+            None => true,
+        };
+        if !due && !finished {
+            return None;
+        }
+        self.sample_rate(now);
+        self.rendered = Some(now);
+        Some(self.render())
+    }
+
+    fn sample_rate(&mut self, now: Duration) {
+        let (time, position) = self.sample.unwrap_or((self.start, 0));
+        let elapsed = now.saturating_sub(time).as_secs_f64();
+        if elapsed <= 0.0 {
+            self.sample.get_or_insert((now, self.position));
+            return;
+        }
+        let rate = self.position.saturating_sub(position) as f64 / elapsed;
+        self.rate = Some(match self.rate {
+            Some(smoothed) => self.smoothing * rate + (1.0 - self.smoothing) * smoothed,
+            None => rate,
+        });
+        self.sample = Some((now, self.position));
+    }
+
+    /// The smoothed number of items per second, `None` before the first
+    /// sample.
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// The time since the formatter was created.
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start)
+    }
+
+    /// The remaining time at the smoothed rate, `None` when the length is
+    /// unknown or nothing progressed.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.length?.saturating_sub(self.position);
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+        match self.rate {
+            Some(rate) if rate > 0.0 => Duration::try_from_secs_f64(remaining as f64 / rate).ok(),
+            _ => None,
+        }
+    }
+
+    /// Render the template with the current values, without throttling.
+    pub fn render(&self) -> String {
+        let unknown = || "?".to_string();
+        let mut text = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            text.push_str(&rest[..open]);
+            let placeholder = &rest[open..];
+            let close = match placeholder.find('}') {
+                Some(close) => close,
+                None => {
+                    rest = placeholder;
+                    break;
+                }
+            };
+            let value = match &placeholder[1..close] {
+                "pos" => self.position.to_string(),
+                "len" => self
+                    .length
+                    .map_or_else(unknown, |length| length.to_string()),
+                "percent" => match self.length {
+                    Some(0) => "100%".to_string(),
+                    Some(length) => format!(
+                        "{}%",
+                        self.position.min(length) as u128 * 100 / length as u128
+                    ),
+                    None => unknown(),
+                },
+                "bytes" => format_bytes(self.position),
+                "total_bytes" => self.length.map_or_else(unknown, format_bytes),
+                "per_sec" => self
+                    .rate
+                    .map_or_else(unknown, |rate| format!("{:.1}/s", rate)),
+                "bytes_per_sec" => self
+                    .rate
+                    .map_or_else(unknown, |rate| format!("{}/s", format_bytes(rate as u64))),
+                "elapsed" => format_duration(self.elapsed()),
+                "eta" => self.eta().map_or_else(unknown, format_duration),
+                _ => placeholder[..=close].to_string(),
+            };
+            text.push_str(&value);
+            rest = &placeholder[close + 1..];
+        }
+        text.push_str(rest);
+        text
+    }
+}
+
+/** Format a number of bytes with binary units.
+
+```
+use win_task_dialog::format_bytes;
+
+assert_eq!(format_bytes(512), "512 B");
+assert_eq!(format_bytes(1536), "1.5 KiB");
+assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+```
+*/
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/** Format a duration as `m:ss`, or `h:mm:ss` from one hour.
+
+```
+use std::time::Duration;
+use win_task_dialog::format_duration;
+
+assert_eq!(format_duration(Duration::from_secs(65)), "1:05");
+assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+```
+*/
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
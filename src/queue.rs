@@ -0,0 +1,487 @@
+use crate::*;
+use std::{
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+/// Which queued dialogs are shown as one dialog.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Coalesce {
+    /// The dialogs with the same window title, main instruction and content.
+    #[default]
+    SameText,
+    /// The dialogs with the same key.
+    Key(String),
+    /// The dialog is always shown on its own.
+    Never,
+}
+
+/// The options of `DialogQueue::push_with`.
+#[derive(Clone, Debug, Default)]
+pub struct QueueOptions {
+    /// The dialogs with a higher priority are shown first, the dialogs with
+    /// the same priority in the order they were pushed.
+    pub priority: i32,
+    pub coalesce: Coalesce,
+}
+
+#[derive(Default)]
+struct SlotState {
+    result: Option<Result<TaskDialogResult, TaskDialogError>>,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct Slot {
+    state: Mutex<SlotState>,
+    done: Condvar,
+}
+
+impl Slot {
+    fn state(&self) -> MutexGuard<'_, SlotState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn complete(&self, result: Result<TaskDialogResult, TaskDialogError>) {
+        let waker = {
+            let mut state = self.state();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        self.done.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// The result of a queued dialog, wait for it with `wait` or `.await`.
+pub struct QueuedDialog {
+    slot: Arc<Slot>,
+}
+
+impl QueuedDialog {
+    /// The dialog was closed.
+    pub fn is_done(&self) -> bool {
+        self.slot.state().result.is_some()
+    }
+
+    /// Block until the dialog is closed. The coalesced dialogs report the
+    /// result of the dialog which was shown.
+    pub fn wait(self) -> Result<TaskDialogResult, TaskDialogError> {
+        let mut state = self.slot.state();
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self
+                .slot
+                .done
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl Future for QueuedDialog {
+    type Output = Result<TaskDialogResult, TaskDialogError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct Request {
+    conf: TaskDialogConfig,
+    priority: i32,
+    /// The order of the push.
+    seq: u64,
+    key: Option<String>,
+    slots: Vec<Arc<Slot>>,
+}
+
+/// The dialog shown by the presenter.
+struct Current {
+    key: Option<String>,
+    /// The content before the coalesced text is appended.
+    content: String,
+    handle: TaskDialogHandle,
+    slots: Vec<Arc<Slot>>,
+}
+
+struct QueueState {
+    backend: Option<Box<dyn DialogBackend + Send>>,
+    pending: Vec<Request>,
+    current: Option<Current>,
+    next_seq: u64,
+    coalesced_text: fn(usize) -> String,
+}
+
+/** Shows the dialogs pushed from any thread one at a time, on a presenter
+thread which runs while dialogs are queued.
+
+A dialog pushed while an identical dialog is queued or shown is not shown
+again, the count of the coalesced dialogs is appended to the content.
+
+```
+use win_task_dialog::*;
+
+let queue = DialogQueue::with_backend(|_conf: &mut TaskDialogConfig| {
+    Ok(TaskDialogResult {
+        button_id: IDOK,
+        ..Default::default()
+    })
+});
+let first = queue.push(TaskDialogConfig {
+    main_instruction: "The file could not be saved".to_string(),
+    ..Default::default()
+});
+let urgent = queue.push_with(
+    TaskDialogConfig {
+        main_instruction: "The disk is full".to_string(),
+        ..Default::default()
+    },
+    QueueOptions {
+        priority: 10,
+        ..Default::default()
+    },
+);
+assert_eq!(first.wait().unwrap().button_id, IDOK);
+assert_eq!(urgent.wait().unwrap().button_id, IDOK);
+```
+*/
+#[derive(Clone)]
+pub struct DialogQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl Default for DialogQueue {
+    fn default() -> Self {
+        DialogQueue::new()
+    }
+}
+
+impl DialogQueue {
+    /// A queue which shows the dialogs with `show_task_dialog`.
+    pub fn new() -> Self {
+        DialogQueue::with_backend(NativeBackend)
+    }
+
+    pub fn with_backend<B: DialogBackend + Send + 'static>(backend: B) -> Self {
+        DialogQueue {
+            state: Arc::new(Mutex::new(QueueState {
+                backend: Some(Box::new(backend)),
+                pending: Vec::new(),
+                current: None,
+                next_seq: 0,
+                coalesced_text: |count| format!("({} more like this)", count),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The text appended to the content for the count of the coalesced
+    /// dialogs, `(3 more like this)` by default.
+    pub fn set_coalesced_text(&self, text: fn(usize) -> String) {
+        self.state().coalesced_text = text;
+    }
+
+    /// The number of dialogs waiting to be shown, not counting the
+    /// coalesced dialogs.
+    pub fn len(&self) -> usize {
+        self.state().pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state().pending.is_empty()
+    }
+
+    /// Queue the dialog with the default options.
+    pub fn push(&self, conf: TaskDialogConfig) -> QueuedDialog {
+        self.push_with(conf, QueueOptions::default())
+    }
+
+    /// Queue the dialog, it is shown when the dialogs with a higher or the
+    /// same priority which were pushed before it are closed.
+    pub fn push_with(&self, conf: TaskDialogConfig, options: QueueOptions) -> QueuedDialog {
+        let slot = Arc::new(Slot::default());
+        let key = match options.coalesce {
+            Coalesce::SameText => Some(format!(
+                "{}\0{}\0{}",
+                conf.window_title, conf.main_instruction, conf.content
+            )),
+            Coalesce::Key(key) => Some(key),
+            Coalesce::Never => None,
+        };
+
+        let mut state = self.state();
+        let coalesced_text = state.coalesced_text;
+
+        // Coalesce with the shown dialog, the content is updated. A dialog
+        // which is destroyed is not shown again, the request is queued.
+        if let Some(current) = state
+            .current
+            .as_mut()
+            .filter(|current| key.is_some() && current.key == key && !current.handle.is_finished())
+        {
+            current.slots.push(slot.clone());
+            let content =
+                with_coalesced_text(&current.content, current.slots.len(), coalesced_text);
+            let handle = current.handle.clone();
+            // The lock is not held while the dialog handles the message
+            drop(state);
+            handle.set_content(&content);
+            return QueuedDialog { slot };
+        }
+
+        // Coalesce with a queued dialog, which keeps the higher priority
+        if let Some(request) = state
+            .pending
+            .iter_mut()
+            .find(|request| key.is_some() && request.key == key)
+        {
+            request.slots.push(slot.clone());
+            request.priority = request.priority.max(options.priority);
+            return QueuedDialog { slot };
+        }
+
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.pending.push(Request {
+            conf,
+            priority: options.priority,
+            seq,
+            key,
+            slots: vec![slot.clone()],
+        });
+
+        // Start the presenter, unless it is running
+        if let Some(backend) = state.backend.take() {
+            let queue = self.clone();
+            std::thread::Builder::new()
+                .name("task-dialog-queue".to_string())
+                .spawn(move || queue.present(backend))
+                .expect("failed to spawn the dialog queue thread");
+        }
+        QueuedDialog { slot }
+    }
+
+    /// Show the queued dialogs until the queue is empty, then give the
+    /// backend back to the queue.
+    fn present(&self, mut backend: Box<dyn DialogBackend + Send>) {
+        loop {
+            let (mut request, handle) = {
+                let mut state = self.state();
+                let next = state
+                    .pending
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, request)| (request.priority, std::cmp::Reverse(request.seq)))
+                    .map(|(i, _)| i);
+                let next = match next {
+                    Some(next) => next,
+                    None => {
+                        state.backend = Some(backend);
+                        return;
+                    }
+                };
+                let mut request = state.pending.remove(next);
+                let handle = request
+                    .conf
+                    .handle
+                    .get_or_insert_with(TaskDialogHandle::new)
+                    .clone();
+                // A handle of a dialog shown before is not finished until it
+                // is destroyed again
+                handle.begin();
+                let content = request.conf.content.clone();
+                request.conf.content =
+                    with_coalesced_text(&content, request.slots.len(), state.coalesced_text);
+                state.current = Some(Current {
+                    key: request.key.take(),
+                    content,
+                    handle: handle.clone(),
+                    slots: std::mem::take(&mut request.slots),
+                });
+                (request, handle)
+            };
+
+            let result =
+                std::panic::catch_unwind(AssertUnwindSafe(|| backend.show(&mut request.conf)))
+                    .unwrap_or_else(|_| Err(TaskDialogError::other("the dialog backend panicked")));
+
+            let slots = match self.state().current.take() {
+                Some(current) if current.handle.same_dialog(&handle) => current.slots,
+                _ => Vec::new(),
+            };
+            for slot in slots.iter() {
+                slot.complete(match &result {
+                    Ok(result) => Ok(result.clone()),
+                    Err(e) => Err(TaskDialogError::new(e.kind(), e.to_string())),
+                });
+            }
+        }
+    }
+}
+
+/// The content with the count of the other dialogs, the content when there
+/// is one dialog.
+fn with_coalesced_text(content: &str, dialogs: usize, text: fn(usize) -> String) -> String {
+    match dialogs {
+        0 | 1 => content.to_string(),
+        dialogs if content.is_empty() => text(dialogs - 1),
+        dialogs => format!("{}\n\n{}", content, text(dialogs - 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    /// A queue which reports the main instruction and the content of the
+    /// shown dialogs, and blocks each dialog until it is released. The
+    /// dialogs are destroyed before they are released when `destroy` is set.
+    fn blocking_queue(destroy: bool) -> (DialogQueue, Receiver<(String, String)>, Sender<()>) {
+        let (shown, shown_rx) = channel();
+        let (release, release_rx) = channel::<()>();
+        let queue = DialogQueue::with_backend(move |conf: &mut TaskDialogConfig| {
+            if destroy {
+                conf.handle.as_ref().unwrap().detach();
+            }
+            shown
+                .send((conf.main_instruction.clone(), conf.content.clone()))
+                .unwrap();
+            release_rx.recv().unwrap();
+            Ok(TaskDialogResult {
+                button_id: conf.main_instruction.len() as i32,
+                ..Default::default()
+            })
+        });
+        (queue, shown_rx, release)
+    }
+
+    fn dialog(main_instruction: &str) -> TaskDialogConfig {
+        TaskDialogConfig {
+            main_instruction: main_instruction.to_string(),
+            content: "content".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn with_priority(priority: i32) -> QueueOptions {
+        QueueOptions {
+            priority,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn higher_priorities_are_shown_first() {
+        let (queue, shown, release) = blocking_queue(false);
+        let first = queue.push(dialog("first"));
+        assert_eq!(shown.recv().unwrap().0, "first");
+
+        let low = queue.push_with(dialog("low"), with_priority(0));
+        let high = queue.push_with(dialog("high"), with_priority(10));
+        let medium = queue.push_with(dialog("medium"), with_priority(5));
+        let low_again = queue.push_with(dialog("low again"), with_priority(0));
+        assert_eq!(queue.len(), 4);
+
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            release.send(()).unwrap();
+            order.push(shown.recv().unwrap().0);
+        }
+        release.send(()).unwrap();
+        assert_eq!(order, ["high", "medium", "low", "low again"]);
+
+        for (dialog, main_instruction) in [
+            (first, "first"),
+            (low, "low"),
+            (high, "high"),
+            (medium, "medium"),
+            (low_again, "low again"),
+        ] {
+            assert_eq!(
+                dialog.wait().unwrap().button_id,
+                main_instruction.len() as i32
+            );
+        }
+    }
+
+    #[test]
+    fn duplicates_are_counted_in_the_content() {
+        let (queue, shown, release) = blocking_queue(false);
+        let first = queue.push(dialog("first"));
+        assert_eq!(shown.recv().unwrap(), ("first".into(), "content".into()));
+
+        // Coalesced with the shown dialog, the content is updated
+        let shown_twice = queue.push(dialog("first"));
+        let shown_thrice = queue.push(dialog("first"));
+        {
+            let state = queue.state();
+            let current = state.current.as_ref().unwrap();
+            assert_eq!(current.slots.len(), 3);
+            assert_eq!(
+                with_coalesced_text(&current.content, current.slots.len(), state.coalesced_text),
+                "content\n\n(2 more like this)"
+            );
+        }
+
+        // Coalesced with a queued dialog, which keeps the higher priority
+        let second = queue.push_with(dialog("second"), with_priority(0));
+        let third = queue.push_with(dialog("third"), with_priority(5));
+        let second_again = queue.push_with(dialog("second"), with_priority(10));
+        assert_eq!(queue.len(), 2);
+
+        release.send(()).unwrap();
+        assert_eq!(
+            shown.recv().unwrap(),
+            ("second".into(), "content\n\n(1 more like this)".into())
+        );
+        release.send(()).unwrap();
+        assert_eq!(shown.recv().unwrap(), ("third".into(), "content".into()));
+        release.send(()).unwrap();
+
+        for dialog in [first, shown_twice, shown_thrice] {
+            assert_eq!(dialog.wait().unwrap().button_id, 5);
+        }
+        for dialog in [second, second_again] {
+            assert_eq!(dialog.wait().unwrap().button_id, 6);
+        }
+        assert_eq!(third.wait().unwrap().button_id, 5);
+    }
+
+    #[test]
+    fn duplicates_of_a_destroyed_dialog_are_shown_again() {
+        let (queue, shown, release) = blocking_queue(true);
+        let first = queue.push(dialog("first"));
+        assert_eq!(shown.recv().unwrap().0, "first");
+
+        // The dialog is destroyed, but the backend did not return yet
+        let again = queue.push(dialog("first"));
+        assert_eq!(queue.state().current.as_ref().unwrap().slots.len(), 1);
+        assert_eq!(queue.len(), 1);
+
+        release.send(()).unwrap();
+        assert_eq!(shown.recv().unwrap(), ("first".into(), "content".into()));
+        assert!(first.wait().is_ok());
+        assert!(!again.is_done());
+        release.send(()).unwrap();
+        assert!(again.wait().is_ok());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::*;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+/** The task dialogs of the process which are shown, to dismiss them on
+shutdown instead of waiting on a modal dialog.
+
+A dialog is registered from `show_task_dialog` until it returns, including
+the time before it is created. The dialogs shown by the `MessageBoxW`
+fallback are not registered.
+
+```no_run
+use std::time::Duration;
+use win_task_dialog::*;
+
+let registry = DialogRegistry::global();
+registry.close_all(IDCANCEL);
+if !registry.wait_closed(Duration::from_secs(5)) {
+    eprintln!("{} dialogs are still open", registry.len());
+}
+```
+*/
+pub struct DialogRegistry {
+    handles: Mutex<Vec<TaskDialogHandle>>,
+    closed: Condvar,
+}
+
+static REGISTRY: OnceLock<DialogRegistry> = OnceLock::new();
+
+impl DialogRegistry {
+    /// The registry of the process.
+    pub fn global() -> &'static DialogRegistry {
+        REGISTRY.get_or_init(|| DialogRegistry {
+            handles: Mutex::new(Vec::new()),
+            closed: Condvar::new(),
+        })
+    }
+
+    fn handles(&self) -> MutexGuard<'_, Vec<TaskDialogHandle>> {
+        self.handles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The handles of the shown dialogs.
+    pub fn dialogs(&self) -> Vec<TaskDialogHandle> {
+        self.handles().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles().is_empty()
+    }
+
+    /// Close all the shown dialogs and report `outcome` as the clicked
+    /// button, see `TaskDialogHandle::close`. Returns the number of dialogs.
+    pub fn close_all(&self, outcome: i32) -> usize {
+        let handles = self.dialogs();
+        for handle in handles.iter() {
+            handle.close(outcome);
+        }
+        handles.len()
+    }
+
+    /// Wait until no dialog is shown, `false` on timeout.
+    pub fn wait_closed(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut handles = self.handles();
+        while !handles.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            handles = match self.closed.wait_timeout(handles, deadline - now) {
+                Ok((handles, _)) => handles,
+                Err(e) => e.into_inner().0,
+            };
+        }
+        true
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn register(&self, handle: &TaskDialogHandle) {
+        self.handles().push(handle.clone());
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn unregister(&self, handle: &TaskDialogHandle) {
+        let mut handles = self.handles();
+        handles.retain(|h| !h.same_dialog(handle));
+        if handles.is_empty() {
+            self.closed.notify_all();
+        }
+    }
+}
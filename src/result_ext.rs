@@ -0,0 +1,160 @@
+use crate::*;
+use std::error::Error;
+
+/// Show the error of a `Result` in an error dialog, with the `source()`
+/// chain of the error in the expandable area.
+pub trait ResultExt<T, E> {
+    /// Show the error and return the result unchanged.
+    fn or_show_error(self, title: &str) -> Result<T, E>;
+
+    /// Same as `or_show_error`, showing the dialog with `backend`.
+    fn or_show_error_in<B: DialogBackend + ?Sized>(
+        self,
+        backend: &mut B,
+        title: &str,
+    ) -> Result<T, E>;
+
+    /// Show the error with Retry and Cancel buttons, call `retry` until it
+    /// succeeds or the user cancels, and return the last result.
+    fn or_ask_retry<F: FnMut() -> Result<T, E>>(self, retry: F) -> Result<T, E>;
+
+    /// Same as `or_ask_retry`, showing the dialogs with `backend`. A dialog
+    /// which cannot be shown cancels.
+    fn or_ask_retry_in<B, F>(self, backend: &mut B, retry: F) -> Result<T, E>
+    where
+        B: DialogBackend + ?Sized,
+        F: FnMut() -> Result<T, E>;
+
+    /// Show the error and exit the process with `code`.
+    fn or_show_and_exit(self, code: i32) -> T;
+}
+
+impl<T, E: Error> ResultExt<T, E> for Result<T, E> {
+    fn or_show_error(self, title: &str) -> Result<T, E> {
+        self.or_show_error_in(&mut NativeBackend, title)
+    }
+
+    fn or_show_error_in<B: DialogBackend + ?Sized>(
+        self,
+        backend: &mut B,
+        title: &str,
+    ) -> Result<T, E> {
+        if let Err(err) = &self {
+            backend.show(&mut error_config(title, err)).ok();
+        }
+        self
+    }
+
+    fn or_ask_retry<F: FnMut() -> Result<T, E>>(self, retry: F) -> Result<T, E> {
+        self.or_ask_retry_in(&mut NativeBackend, retry)
+    }
+
+    fn or_ask_retry_in<B, F>(self, backend: &mut B, mut retry: F) -> Result<T, E>
+    where
+        B: DialogBackend + ?Sized,
+        F: FnMut() -> Result<T, E>,
+    {
+        let mut result = self;
+        while let Err(err) = &result {
+            let mut conf = TaskDialogConfig {
+                common_buttons: TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON,
+                ..error_config("", err)
+            };
+            match backend.show(&mut conf) {
+                Ok(r) if r.button_id == IDRETRY => result = retry(),
+                _ => break,
+            }
+        }
+        result
+    }
+
+    fn or_show_and_exit(self, code: i32) -> T {
+        match self {
+            Ok(value) => value,
+            Err(err) => {
+                show_task_dialog(&mut error_config("", &err)).ok();
+                std::process::exit(code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Error;
+
+    fn failed(message: &str) -> Result<u32, Error> {
+        Err(Error::other(message.to_string()))
+    }
+
+    fn clicked(button_id: i32) -> Result<TaskDialogResult, Error> {
+        Ok(TaskDialogResult {
+            button_id,
+            ..Default::default()
+        })
+    }
+
+    fn no_dialog(_: &mut TaskDialogConfig) -> Result<TaskDialogResult, Error> {
+        panic!("no dialog is shown")
+    }
+
+    #[test]
+    fn or_show_error_shows_the_error_and_keeps_the_result() {
+        let mut shown = vec![];
+        let mut backend = |conf: &mut TaskDialogConfig| {
+            shown.push((conf.window_title.clone(), conf.main_instruction.clone()));
+            clicked(IDOK)
+        };
+        let result = failed("The disk is full").or_show_error_in(&mut backend, "Save");
+        assert_eq!(result.unwrap_err().to_string(), "The disk is full");
+        assert_eq!(
+            shown,
+            vec![("Save".to_string(), "The disk is full".to_string())]
+        );
+
+        let result = Ok::<u32, Error>(7).or_show_error_in(&mut no_dialog, "Save");
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[test]
+    fn or_ask_retry_retries_until_ok() {
+        let mut attempts = 0;
+        let retry = || {
+            attempts += 1;
+            if attempts < 2 {
+                failed("The network is down")
+            } else {
+                Ok(attempts)
+            }
+        };
+        let mut dialogs = 0;
+        let mut backend = |conf: &mut TaskDialogConfig| {
+            dialogs += 1;
+            assert_eq!(
+                conf.common_buttons,
+                TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON
+            );
+            clicked(IDRETRY)
+        };
+        let result = failed("The network is down").or_ask_retry_in(&mut backend, retry);
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(dialogs, 2);
+
+        let result = Ok::<u32, Error>(7).or_ask_retry_in(&mut no_dialog, || failed("unused"));
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[test]
+    fn or_ask_retry_returns_the_last_error_on_cancel() {
+        let mut buttons = vec![IDCANCEL, IDRETRY];
+        let mut backend = |_: &mut TaskDialogConfig| clicked(buttons.pop().unwrap());
+        let result = failed("first").or_ask_retry_in(&mut backend, || failed("second"));
+        assert_eq!(result.unwrap_err().to_string(), "second");
+
+        // A dialog which cannot be shown cancels.
+        let mut failing = |_: &mut TaskDialogConfig| Err(Error::other("no desktop"));
+        let result = failed("first").or_ask_retry_in(&mut failing, || Ok(1));
+        assert_eq!(result.unwrap_err().to_string(), "first");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::*;
+use std::error::Error;
+
+/// The outcome of `retry_with_dialog`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryOutcome<T> {
+    /// The operation succeeded.
+    Ok(T),
+    /// The user chose to skip the operation.
+    Skipped,
+    /// The user cancelled, or the dialog could not be shown.
+    Cancelled,
+}
+
+/// A choice remembered by the "Apply to all" checkbox.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryChoice {
+    Skip,
+    Cancel,
+}
+
+/// The options of `retry_with_dialog`, reuse the same options for all
+/// operations of a batch to remember the "Apply to all" choice.
+pub struct RetryOptions {
+    /// Uses the window title of `set_dialog_defaults` when empty.
+    pub window_title: String,
+    pub main_instruction: String,
+    /// Show a Skip button next to Retry and Cancel.
+    pub skip_button: bool,
+    /// The text of the "Apply to all" checkbox, no checkbox when empty.
+    pub apply_to_all_text: String,
+    /** When the user checks "Apply to all", the value set to the choice and
+    the next failures are skipped or cancelled without a dialog. */
+    pub applied_to_all: Option<RetryChoice>,
+}
+
+impl RetryOptions {
+    /// The button ID of the Skip button.
+    pub const SKIP_BUTTON_ID: i32 = 100;
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        RetryOptions {
+            window_title: "".to_string(),
+            main_instruction: "The operation failed".to_string(),
+            skip_button: false,
+            apply_to_all_text: "".to_string(),
+            applied_to_all: None,
+        }
+    }
+}
+
+/// Run `op` and show the error with Retry and Cancel buttons when it fails,
+/// until it succeeds, or the user skips or cancels.
+pub fn retry_with_dialog<T, E, F>(op: F, options: &mut RetryOptions) -> RetryOutcome<T>
+where
+    E: Error,
+    F: FnMut() -> Result<T, E>,
+{
+    retry_with_dialog_in(&mut NativeBackend, op, options)
+}
+
+/** Same as `retry_with_dialog`, showing the dialogs with `backend`.
+
+```
+use std::io::{Error, ErrorKind};
+use win_task_dialog::*;
+
+let fail = || Err::<(), _>(Error::new(ErrorKind::Other, "The network is down"));
+let mut options = RetryOptions {
+    skip_button: true,
+    apply_to_all_text: "Apply to all".to_string(),
+    ..Default::default()
+};
+
+// Retry, then Skip with "Apply to all" checked.
+let mut details = vec![];
+let mut buttons = vec![RetryOptions::SKIP_BUTTON_ID, IDRETRY];
+let mut backend = |conf: &mut TaskDialogConfig| {
+    details.push(conf.expander.as_ref().unwrap().text.clone());
+    Ok(TaskDialogResult {
+        button_id: buttons.pop().unwrap(),
+        checked: buttons.is_empty(),
+        ..Default::default()
+    })
+};
+let outcome = retry_with_dialog_in(&mut backend, fail, &mut options);
+assert_eq!(outcome, RetryOutcome::Skipped);
+assert!(details[0].starts_with("Attempt: 1"));
+assert!(details[1].starts_with("Attempt: 2"));
+assert_eq!(options.applied_to_all, Some(RetryChoice::Skip));
+
+// The next failures are skipped without a dialog.
+let mut no_dialog = |_: &mut TaskDialogConfig| -> Result<TaskDialogResult, Error> {
+    panic!("no dialog is shown")
+};
+let outcome = retry_with_dialog_in(&mut no_dialog, fail, &mut options);
+assert_eq!(outcome, RetryOutcome::Skipped);
+
+// A dialog which cannot be shown cancels.
+let mut failing = |_: &mut TaskDialogConfig| Err(Error::new(ErrorKind::Other, "no desktop"));
+let outcome = retry_with_dialog_in(&mut failing, fail, &mut RetryOptions::default());
+assert_eq!(outcome, RetryOutcome::Cancelled);
+```
+*/
+pub fn retry_with_dialog_in<B, T, E, F>(
+    backend: &mut B,
+    mut op: F,
+    options: &mut RetryOptions,
+) -> RetryOutcome<T>
+where
+    B: DialogBackend + ?Sized,
+    E: Error,
+    F: FnMut() -> Result<T, E>,
+{
+    let mut attempt = 1;
+    loop {
+        let err = match op() {
+            Ok(value) => return RetryOutcome::Ok(value),
+            Err(err) => err,
+        };
+        match options.applied_to_all {
+            Some(RetryChoice::Skip) => return RetryOutcome::Skipped,
+            Some(RetryChoice::Cancel) => return RetryOutcome::Cancelled,
+            None => {}
+        }
+
+        let mut details = format!("Attempt: {}", attempt);
+        let chain = error_chain(&err);
+        if !chain.is_empty() {
+            details.push('\n');
+            details.push_str(&chain);
+        }
+        let mut conf = TaskDialogConfig::from_defaults();
+        if !options.window_title.is_empty() {
+            conf.window_title = options.window_title.clone();
+        }
+        conf.common_buttons = TDCBF_RETRY_BUTTON | TDCBF_CANCEL_BUTTON;
+        conf.main_icon = TD_WARNING_ICON;
+        conf.main_instruction = options.main_instruction.clone();
+        conf.content = err.to_string();
+        conf.verification_text = options.apply_to_all_text.clone();
+        conf.expander = Some(Expander {
+            text: details,
+            ..Default::default()
+        });
+        if options.skip_button {
+            conf.buttons = vec![TaskDialogButton::new(RetryOptions::SKIP_BUTTON_ID, "Skip")];
+        }
+
+        let result = match backend.show(&mut conf) {
+            Ok(result) => result,
+            Err(_) => return RetryOutcome::Cancelled,
+        };
+        let choice = match result.button_id {
+            IDRETRY => {
+                attempt += 1;
+                continue;
+            }
+            RetryOptions::SKIP_BUTTON_ID => RetryChoice::Skip,
+            _ => RetryChoice::Cancel,
+        };
+        if result.checked {
+            options.applied_to_all = Some(choice);
+        }
+        return match choice {
+            RetryChoice::Skip => RetryOutcome::Skipped,
+            RetryChoice::Cancel => RetryOutcome::Cancelled,
+        };
+    }
+}
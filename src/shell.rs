@@ -0,0 +1,99 @@
+#[cfg(windows)]
+use widestring::U16CString;
+#[cfg(windows)]
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{GlobalFree, HANDLE, HWND},
+        System::{
+            DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+            Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+        },
+        UI::{
+            Controls::Dialogs::{
+                GetSaveFileNameW, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+            },
+            Shell::ShellExecuteW,
+            WindowsAndMessaging::SW_SHOWNORMAL,
+        },
+    },
+};
+
+#[cfg(windows)]
+use std::{io::Error, path::PathBuf};
+
+#[cfg(windows)]
+const CF_UNICODETEXT: u32 = 13;
+
+/** Open the link of a hyperlink with the default browser,
+can be used as `TaskDialogConfig::hyperlink_callback`. */
+#[cfg(windows)]
+pub fn open_hyperlink(context: &str) {
+    unsafe {
+        let url = U16CString::from_str_unchecked(context);
+        ShellExecuteW(
+            None,
+            windows::core::w!("open"),
+            PCWSTR(url.as_ptr()),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+pub fn open_hyperlink(_context: &str) {}
+
+/// Put the text on the clipboard.
+#[cfg(windows)]
+pub(crate) fn copy_to_clipboard(hwnd: HWND, text: &str) -> Result<(), Error> {
+    let text: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+    unsafe {
+        OpenClipboard(Some(hwnd))?;
+        let result = (|| {
+            EmptyClipboard()?;
+            let mem = GlobalAlloc(GMEM_MOVEABLE, text.len() * 2)?;
+            let ptr = GlobalLock(mem) as *mut u16;
+            if ptr.is_null() {
+                GlobalFree(Some(mem)).ok();
+                return Err(windows::core::Error::from_win32());
+            }
+            std::ptr::copy_nonoverlapping(text.as_ptr(), ptr, text.len());
+            GlobalUnlock(mem).ok();
+            // The clipboard owns the memory once SetClipboardData succeeds.
+            if let Err(e) = SetClipboardData(CF_UNICODETEXT, Some(HANDLE(mem.0))) {
+                GlobalFree(Some(mem)).ok();
+                return Err(e);
+            }
+            Ok(())
+        })();
+        CloseClipboard().ok();
+        result.map_err(Error::from)
+    }
+}
+
+/// Ask for a file to save to, `None` when the user cancelled.
+#[cfg(windows)]
+pub(crate) fn save_file_dialog(hwnd: HWND, file_name: &str) -> Option<PathBuf> {
+    let mut buffer = vec![0u16; 1024];
+    for (i, c) in file_name.encode_utf16().take(buffer.len() - 1).enumerate() {
+        buffer[i] = c;
+    }
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: hwnd,
+        lpstrFilter: windows::core::w!("Text Files (*.txt)\0*.txt\0All Files (*.*)\0*.*\0"),
+        lpstrFile: PWSTR(buffer.as_mut_ptr()),
+        nMaxFile: buffer.len() as u32,
+        lpstrDefExt: windows::core::w!("txt"),
+        Flags: OFN_OVERWRITEPROMPT | OFN_PATHMUSTEXIST,
+        ..Default::default()
+    };
+    if unsafe { GetSaveFileNameW(&mut ofn) }.as_bool() {
+        let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+        Some(PathBuf::from(String::from_utf16_lossy(&buffer[..len])))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,108 @@
+use crate::*;
+use std::{io::Error, thread::JoinHandle};
+
+/// A dialog shown on its own thread, see `spawn_task_dialog`.
+pub struct RunningDialog {
+    handle: TaskDialogHandle,
+    thread: JoinHandle<Result<TaskDialogResult, Error>>,
+}
+
+impl RunningDialog {
+    /// The handle to update the dialog.
+    pub fn handle(&self) -> &TaskDialogHandle {
+        &self.handle
+    }
+
+    /// The dialog is created and not yet closed.
+    pub fn is_open(&self) -> bool {
+        self.handle.is_open()
+    }
+
+    /// The dialog thread returned, `join` does not wait.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Close the dialog and report `outcome` as the clicked button.
+    pub fn close(&self, outcome: i32) {
+        self.handle.close(outcome);
+    }
+
+    /** Wait until the dialog is closed.
+
+    The messages of the calling thread are dispatched while waiting. The
+    dialog enables and disables its owner window with messages sent to the
+    thread of the window, which would otherwise never return when the owner
+    belongs to the calling thread. */
+    pub fn join(self) -> Result<TaskDialogResult, Error> {
+        let thread = self.thread;
+        wait_dispatching(|| thread.is_finished().then_some(()));
+        thread
+            .join()
+            .unwrap_or_else(|_| Err(Error::other("the dialog thread panicked")))
+    }
+}
+
+/** Show the dialog on a new thread, which runs the message loop of the
+dialog, and return without waiting for the dialog to close.
+
+The config is moved to the dialog thread, update the dialog through
+`RunningDialog::handle`. Returns an error when the thread could not be
+created. */
+pub fn spawn_task_dialog(mut conf: TaskDialogConfig) -> Result<RunningDialog, Error> {
+    let handle = conf
+        .handle
+        .get_or_insert_with(TaskDialogHandle::new)
+        .clone();
+    // A `close` before the dialog thread shows the dialog is not ignored
+    handle.begin();
+    let thread = std::thread::Builder::new()
+        .name("task-dialog".to_string())
+        .spawn(move || show_task_dialog(&mut conf))?;
+    Ok(RunningDialog { handle, thread })
+}
+
+/** Wait until `ready` returns a value, dispatching the messages of the
+calling thread meanwhile. The windows of the thread keep handling the
+messages sent to them from the dialog thread. */
+#[cfg(windows)]
+pub(crate) fn wait_dispatching<T>(mut ready: impl FnMut() -> Option<T>) -> T {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, MsgWaitForMultipleObjects, PeekMessageW, PostQuitMessage,
+        TranslateMessage, MSG, PM_REMOVE, QS_ALLINPUT, WM_QUIT,
+    };
+
+    let mut quit = None;
+    let value = loop {
+        if let Some(value) = ready() {
+            break value;
+        }
+        unsafe {
+            MsgWaitForMultipleObjects(None, false, 10, QS_ALLINPUT);
+            let mut msg = MSG::default();
+            while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                // The message loop of the caller exits once the wait is over
+                if msg.message == WM_QUIT {
+                    quit = Some(msg.wParam.0 as i32);
+                    continue;
+                }
+                TranslateMessage(&msg).ok().ok();
+                DispatchMessageW(&msg);
+            }
+        }
+    };
+    if let Some(code) = quit {
+        unsafe { PostQuitMessage(code) };
+    }
+    value
+}
+
+#[cfg(not(windows))]
+pub(crate) fn wait_dispatching<T>(mut ready: impl FnMut() -> Option<T>) -> T {
+    loop {
+        if let Some(value) = ready() {
+            return value;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+}
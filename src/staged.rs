@@ -0,0 +1,161 @@
+use crate::*;
+
+/// The steps of the progress bar of `StagedProgress`.
+const STEPS: u64 = 10_000;
+
+/// A stage of `StagedProgress`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    /// Shown as the main instruction while the stage runs.
+    pub label: String,
+    /// The share of the stage in the progress bar, relative to the weights
+    /// of the other stages.
+    pub weight: f64,
+}
+
+impl Stage {
+    pub fn new(label: &str, weight: f64) -> Self {
+        Stage {
+            label: label.to_string(),
+            weight,
+        }
+    }
+}
+
+/** Drives the progress bar of a progress dialog through weighted stages,
+each with its own progress from 0 to 1.
+
+```
+use win_task_dialog::*;
+
+ProgressDialog::run(TaskDialogConfig::default(), |progress, _cancel| {
+    let mut stages = StagedProgress::new(
+        progress,
+        vec![
+            Stage::new("Downloading", 6.0),
+            Stage::new("Verifying", 1.0),
+            Stage::new("Extracting", 3.0),
+        ],
+    );
+    stages.next_stage();
+    stages.set_stage_progress(0.5);
+    assert_eq!(stages.overall(), 0.3);
+    stages.next_stage();
+    stages.set_stage_indeterminate();
+    assert_eq!(stages.overall(), 0.6);
+    stages.next_stage();
+    stages.set_stage_progress(1.0);
+    assert_eq!(stages.overall(), 1.0);
+    assert!(!stages.next_stage());
+})
+.unwrap();
+```
+*/
+pub struct StagedProgress<'a> {
+    progress: &'a mut Progress,
+    stages: Vec<Stage>,
+    current: Option<usize>,
+    /// The progress of the current stage, `None` when it is unknown.
+    fraction: Option<f64>,
+}
+
+impl<'a> StagedProgress<'a> {
+    /// The stages run in order, none is started yet. The position and the
+    /// length of `progress` are set by the stages.
+    pub fn new(progress: &'a mut Progress, stages: Vec<Stage>) -> Self {
+        StagedProgress {
+            progress,
+            stages,
+            current: None,
+            fraction: Some(0.0),
+        }
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// The index of the running stage.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current_stage(&self) -> Option<&Stage> {
+        self.stages.get(self.current?)
+    }
+
+    /** Start the stage at `index`, the previous stages are complete. Shows
+    the label of the stage as the main instruction. Does nothing when there
+    is no stage at `index`. */
+    pub fn start_stage(&mut self, index: usize) {
+        let label = match self.stages.get(index) {
+            Some(stage) => stage.label.clone(),
+            None => return,
+        };
+        self.current = Some(index);
+        self.fraction = Some(0.0);
+        self.progress.handle().set_main_instruction(&label);
+        self.update();
+    }
+
+    /// Start the stage after the running stage, `false` when the running
+    /// stage is the last.
+    pub fn next_stage(&mut self) -> bool {
+        let next = self.current.map_or(0, |current| current + 1);
+        if next >= self.stages.len() {
+            return false;
+        }
+        self.start_stage(next);
+        true
+    }
+
+    /// Set the progress of the running stage, from 0 to 1.
+    pub fn set_stage_progress(&mut self, fraction: f64) {
+        self.fraction = Some(if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        });
+        self.update();
+    }
+
+    /// The length of the running stage is unknown, the bar is a marquee
+    /// until `set_stage_progress`.
+    pub fn set_stage_indeterminate(&mut self) {
+        self.fraction = None;
+        self.update();
+    }
+
+    /// Complete all the stages.
+    pub fn finish(&mut self) {
+        if !self.stages.is_empty() {
+            self.current = Some(self.stages.len() - 1);
+            self.set_stage_progress(1.0);
+        }
+    }
+
+    /// The overall progress from 0 to 1, the stages before the running
+    /// stage are complete, a stage of unknown length counts as 0.
+    pub fn overall(&self) -> f64 {
+        let weight = |stage: &Stage| stage.weight.max(0.0);
+        let total: f64 = self.stages.iter().map(weight).sum();
+        let current = match self.current {
+            Some(current) if total > 0.0 => current,
+            _ => return 0.0,
+        };
+        let done: f64 = self.stages[..current].iter().map(weight).sum::<f64>()
+            + weight(&self.stages[current]) * self.fraction.unwrap_or(0.0);
+        (done / total).min(1.0)
+    }
+
+    fn update(&mut self) {
+        match self.fraction {
+            Some(_) => {
+                let position = (self.overall() * STEPS as f64).round() as u64;
+                self.progress.set_length(Some(STEPS));
+                self.progress.set_position(position);
+            }
+            None => self.progress.set_length(None),
+        }
+    }
+}
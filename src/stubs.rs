@@ -0,0 +1,38 @@
+//! The Win32 types of the API on other platforms, where the dialogs are
+//! not shown, named like the types of the `windows` crate.
+
+#![allow(clippy::upper_case_acronyms, non_camel_case_types)]
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct HWND(pub isize);
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct HMODULE(pub isize);
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PCWSTR(pub *const u16);
+
+impl PCWSTR {
+    pub const fn null() -> Self {
+        PCWSTR(std::ptr::null())
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.0.is_null()
+    }
+}
+
+pub(crate) type WPARAM = usize;
+
+pub(crate) type LPARAM = isize;
+
+pub(crate) type HRESULT = i32;
+
+pub(crate) type TASKDIALOG_NOTIFICATIONS = u32;
+
+pub(crate) type TASKDIALOG_FLAGS = u32;
+
+pub(crate) type TASKDIALOG_COMMON_BUTTON_FLAGS = u32;
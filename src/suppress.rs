@@ -0,0 +1,294 @@
+use crate::*;
+use std::{
+    collections::HashMap,
+    io::Error,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// When a repeated dialog is suppressed, see `DialogSuppressor`.
+#[derive(Clone, Debug, Default)]
+pub struct SuppressionPolicy {
+    /// The minimum time between two dialogs with the same identity.
+    pub min_interval: Option<Duration>,
+    /// The maximum number of dialogs with the same identity per session.
+    pub max_count: Option<u32>,
+    /** The text of a verification checkbox, such as "Don't show again for
+    this session", no checkbox when empty. Not added to dialogs with their
+    own verification text. */
+    pub dont_show_again_text: String,
+    /// Called with the identity and the number of suppressed dialogs of the
+    /// identity, each time a dialog is suppressed.
+    pub on_suppressed: Option<fn(identity: &str, suppressed: u32)>,
+}
+
+#[derive(Default)]
+struct Entry {
+    shown: u32,
+    last_shown: Option<Instant>,
+    suppressed: u32,
+    dont_show_again: bool,
+}
+
+/** Suppresses repeated dialogs with the same identity, the window title
+and the main instruction or an explicit ID. The session is the lifetime of
+the suppressor, or until `reset`.
+
+```
+use std::time::{Duration, Instant};
+use win_task_dialog::*;
+
+let suppressor = DialogSuppressor::new(SuppressionPolicy {
+    min_interval: Some(Duration::from_secs(60)),
+    max_count: Some(2),
+    ..Default::default()
+});
+let start = Instant::now();
+assert!(suppressor.should_show("network", start));
+assert!(!suppressor.should_show("network", start + Duration::from_secs(10)));
+assert!(suppressor.should_show("network", start + Duration::from_secs(70)));
+assert!(!suppressor.should_show("network", start + Duration::from_secs(200)));
+assert_eq!(suppressor.suppressed_count("network"), 2);
+```
+*/
+pub struct DialogSuppressor {
+    policy: SuppressionPolicy,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl DialogSuppressor {
+    pub fn new(policy: SuppressionPolicy) -> Self {
+        DialogSuppressor {
+            policy,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The identity of a dialog without an explicit ID.
+    pub fn identity(conf: &TaskDialogConfig) -> String {
+        format!("{}\0{}", conf.window_title, conf.main_instruction)
+    }
+
+    /** Whether the dialog with the identity is shown at `now`. A shown
+    dialog is counted, a suppressed dialog is counted and reported to
+    `on_suppressed`. */
+    pub fn should_show(&self, identity: &str, now: Instant) -> bool {
+        let suppressed = {
+            let mut entries = self.entries();
+            let entry = entries.entry(identity.to_string()).or_default();
+            let too_soon = match (self.policy.min_interval, entry.last_shown) {
+                (Some(interval), Some(last_shown)) => now.duration_since(last_shown) < interval,
+                _ => false,
+            };
+            let too_many = self.policy.max_count.is_some_and(|max| entry.shown >= max);
+            if entry.dont_show_again || too_soon || too_many {
+                entry.suppressed += 1;
+                Some(entry.suppressed)
+            } else {
+                entry.shown += 1;
+                entry.last_shown = Some(now);
+                None
+            }
+        };
+        match suppressed {
+            Some(count) => {
+                if let Some(on_suppressed) = self.policy.on_suppressed {
+                    on_suppressed(identity, count);
+                }
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Show the dialog with `show_task_dialog` unless it is suppressed,
+    /// `None` when it is suppressed.
+    pub fn show(&self, conf: &mut TaskDialogConfig) -> Result<Option<TaskDialogResult>, Error> {
+        self.show_with(&mut NativeBackend, None, conf)
+    }
+
+    /// Same as `show`, showing the dialog with `backend`, with an explicit
+    /// identity, or the identity of the config when `None`.
+    pub fn show_with<B>(
+        &self,
+        backend: &mut B,
+        identity: Option<&str>,
+        conf: &mut TaskDialogConfig,
+    ) -> Result<Option<TaskDialogResult>, Error>
+    where
+        B: DialogBackend + ?Sized,
+    {
+        let identity = match identity {
+            Some(identity) => identity.to_string(),
+            None => DialogSuppressor::identity(conf),
+        };
+        if !self.should_show(&identity, Instant::now()) {
+            return Ok(None);
+        }
+
+        let dont_show_again =
+            !self.policy.dont_show_again_text.is_empty() && conf.verification_text.is_empty();
+        if dont_show_again {
+            conf.verification_text = self.policy.dont_show_again_text.clone();
+        }
+        let result = backend.show(conf);
+        if dont_show_again {
+            conf.verification_text.clear();
+        }
+
+        let result = result?;
+        if dont_show_again && result.checked {
+            self.entries().entry(identity).or_default().dont_show_again = true;
+        }
+        Ok(Some(result))
+    }
+
+    /// The number of suppressed dialogs with the identity in this session.
+    pub fn suppressed_count(&self, identity: &str) -> u32 {
+        self.entries()
+            .get(identity)
+            .map_or(0, |entry| entry.suppressed)
+    }
+
+    /// The identities with suppressed dialogs and their counts.
+    pub fn suppressed(&self) -> Vec<(String, u32)> {
+        self.entries()
+            .iter()
+            .filter(|(_, entry)| entry.suppressed > 0)
+            .map(|(identity, entry)| (identity.clone(), entry.suppressed))
+            .collect()
+    }
+
+    /// Start a new session, the dialogs are shown again.
+    pub fn reset(&self) {
+        self.entries().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The suppressed dialogs reported to `on_suppressed`.
+    static SUPPRESSED: Mutex<Vec<(String, u32)>> = Mutex::new(Vec::new());
+
+    fn record_suppressed(identity: &str, suppressed: u32) {
+        SUPPRESSED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((identity.to_string(), suppressed));
+    }
+
+    fn reported(identity: &str) -> Vec<u32> {
+        SUPPRESSED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(reported, _)| reported == identity)
+            .map(|(_, suppressed)| *suppressed)
+            .collect()
+    }
+
+    #[test]
+    fn dont_show_again_suppresses_the_later_dialogs() {
+        let suppressor = DialogSuppressor::new(SuppressionPolicy {
+            dont_show_again_text: "Don't show again".to_string(),
+            ..Default::default()
+        });
+        let mut verification_texts = Vec::new();
+        let mut backend = |conf: &mut TaskDialogConfig| {
+            verification_texts.push(conf.verification_text.clone());
+            Ok(TaskDialogResult {
+                button_id: IDOK,
+                checked: verification_texts.len() == 2,
+                ..Default::default()
+            })
+        };
+        let mut conf = TaskDialogConfig {
+            main_instruction: "The update failed".to_string(),
+            ..Default::default()
+        };
+
+        let shown = suppressor.show_with(&mut backend, None, &mut conf).unwrap();
+        assert_eq!(shown.unwrap().button_id, IDOK);
+        assert!(conf.verification_text.is_empty());
+
+        // Checked, the dialog is suppressed from now on
+        let shown = suppressor.show_with(&mut backend, None, &mut conf).unwrap();
+        assert!(shown.unwrap().checked);
+        assert!(conf.verification_text.is_empty());
+        assert!(suppressor
+            .show_with(&mut backend, None, &mut conf)
+            .unwrap()
+            .is_none());
+
+        let identity = DialogSuppressor::identity(&conf);
+        assert_eq!(suppressor.suppressed_count(&identity), 1);
+        assert_eq!(suppressor.suppressed(), [(identity, 1)]);
+
+        suppressor.reset();
+        assert!(suppressor
+            .show_with(&mut backend, None, &mut conf)
+            .unwrap()
+            .is_some());
+        assert_eq!(verification_texts, ["Don't show again"; 3]);
+    }
+
+    #[test]
+    fn own_verification_text_is_kept() {
+        let suppressor = DialogSuppressor::new(SuppressionPolicy {
+            dont_show_again_text: "Don't show again".to_string(),
+            ..Default::default()
+        });
+        let mut backend = |conf: &mut TaskDialogConfig| {
+            assert_eq!(conf.verification_text, "Remember my choice");
+            Ok(TaskDialogResult {
+                checked: true,
+                ..Default::default()
+            })
+        };
+        let mut conf = TaskDialogConfig {
+            verification_text: "Remember my choice".to_string(),
+            ..Default::default()
+        };
+
+        for _ in 0..2 {
+            let shown = suppressor.show_with(&mut backend, Some("choice"), &mut conf);
+            assert!(shown.unwrap().is_some());
+        }
+        assert_eq!(conf.verification_text, "Remember my choice");
+    }
+
+    #[test]
+    fn on_suppressed_reports_the_count() {
+        let suppressor = DialogSuppressor::new(SuppressionPolicy {
+            max_count: Some(1),
+            on_suppressed: Some(record_suppressed),
+            ..Default::default()
+        });
+        let mut shown = 0;
+        let mut backend = |_: &mut TaskDialogConfig| {
+            shown += 1;
+            Ok(TaskDialogResult::default())
+        };
+        let mut conf = TaskDialogConfig::default();
+
+        for _ in 0..3 {
+            suppressor
+                .show_with(&mut backend, Some("on_suppressed"), &mut conf)
+                .unwrap();
+        }
+        assert!(suppressor
+            .show_with(&mut backend, Some("on_suppressed other"), &mut conf)
+            .unwrap()
+            .is_some());
+        assert_eq!(shown, 2);
+        assert_eq!(reported("on_suppressed"), [1, 2]);
+        assert_eq!(reported("on_suppressed other"), [] as [u32; 0]);
+        assert_eq!(suppressor.suppressed_count("on_suppressed"), 2);
+    }
+}
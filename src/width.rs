@@ -0,0 +1,164 @@
+use crate::HWND;
+use std::io::Error;
+
+/// The width of the dialog.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DialogWidth {
+    /// The width chosen by the dialog.
+    #[default]
+    Auto,
+    /// Horizontal dialog units, a quarter of the average character width
+    /// of the dialog font.
+    DialogUnits(u32),
+    /// Pixels at 96 DPI, scaled to the DPI of the monitor of the owner window.
+    Pixels(u32),
+    /// The width of the average character of the dialog font.
+    Characters(u32),
+}
+
+/// The metrics of the dialog font, used to convert pixels to dialog units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FontMetrics {
+    /// The DPI the font metrics are measured at.
+    pub dpi: u32,
+    /// The average character width of the dialog font in pixels, the
+    /// horizontal dialog base unit.
+    pub average_char_width: u32,
+}
+
+impl DialogWidth {
+    /** Convert to dialog units, the value of `TaskDialogConfig::cx_width`.
+    `Auto` is 0, results are rounded to the nearest dialog unit.
+
+    ```
+    use win_task_dialog::*;
+
+    // Segoe UI 9pt has an average character width of 7 pixels at 96 DPI
+    // and of 14 pixels at 192 DPI.
+    let metrics = FontMetrics { dpi: 96, average_char_width: 7 };
+    let high_dpi = FontMetrics { dpi: 192, average_char_width: 14 };
+    assert_eq!(DialogWidth::Pixels(350).to_dialog_units(&metrics), 200);
+    assert_eq!(DialogWidth::Pixels(350).to_dialog_units(&high_dpi), 200);
+    assert_eq!(DialogWidth::Pixels(100).to_dialog_units(&metrics), 57);
+    assert_eq!(DialogWidth::Characters(50).to_dialog_units(&metrics), 200);
+    assert_eq!(DialogWidth::DialogUnits(200).to_dialog_units(&metrics), 200);
+    assert_eq!(DialogWidth::Auto.to_dialog_units(&metrics), 0);
+    ```
+    */
+    pub fn to_dialog_units(self, metrics: &FontMetrics) -> u32 {
+        match self {
+            DialogWidth::Auto => 0,
+            DialogWidth::DialogUnits(units) => units,
+            DialogWidth::Characters(characters) => characters.saturating_mul(4),
+            DialogWidth::Pixels(pixels) => {
+                // pixels * dpi / 96 screen pixels, 4 dialog units per average character.
+                let numerator = pixels as u64 * metrics.dpi as u64 * 4;
+                let denominator = 96 * metrics.average_char_width.max(1) as u64;
+                ((numerator + denominator / 2) / denominator).min(u32::MAX as u64) as u32
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl FontMetrics {
+    /// Measure the message font of the system, which is the dialog font,
+    /// at the system DPI.
+    pub fn system() -> Result<FontMetrics, Error> {
+        FontMetrics::for_window(HWND::default())
+    }
+
+    /** Measure the message font of the system at the DPI of `parent`, the
+    DPI of its monitor for a per-monitor DPI aware process, which is the
+    DPI of the dialog owned by it. The system DPI when `parent` is null or
+    the DPI of a window is unknown, before Windows 10 version 1607. */
+    pub fn for_window(parent: HWND) -> Result<FontMetrics, Error> {
+        use windows::Win32::{
+            Foundation::SIZE,
+            Graphics::Gdi::{
+                CreateFontIndirectW, DeleteObject, GetDC, GetDeviceCaps, GetTextExtentPoint32W,
+                ReleaseDC, SelectObject, LOGPIXELSX,
+            },
+            UI::WindowsAndMessaging::{
+                SystemParametersInfoW, NONCLIENTMETRICSW, SPI_GETNONCLIENTMETRICS,
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+            },
+        };
+
+        unsafe {
+            let mut metrics = NONCLIENTMETRICSW {
+                cbSize: std::mem::size_of::<NONCLIENTMETRICSW>() as u32,
+                ..Default::default()
+            };
+            SystemParametersInfoW(
+                SPI_GETNONCLIENTMETRICS,
+                metrics.cbSize,
+                Some(&mut metrics as *mut _ as _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )?;
+
+            let dc = GetDC(None);
+            let system_dpi = GetDeviceCaps(Some(dc), LOGPIXELSX);
+            // The font of the system metrics is sized for the system DPI.
+            let dpi = window_dpi(parent).unwrap_or(system_dpi);
+            let mut font = metrics.lfMessageFont;
+            if dpi != system_dpi && system_dpi > 0 {
+                font.lfHeight = (font.lfHeight as i64 * dpi as i64 / system_dpi as i64) as i32;
+            }
+            let font = CreateFontIndirectW(&font);
+            let previous = SelectObject(dc, font.into());
+            // The dialog base unit, as computed by the dialog manager.
+            let alphabet: Vec<u16> = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+                .encode_utf16()
+                .collect();
+            let mut size = SIZE::default();
+            let measured = GetTextExtentPoint32W(dc, &alphabet, &mut size).as_bool();
+            SelectObject(dc, previous);
+            DeleteObject(font.into()).ok().ok();
+            ReleaseDC(None, dc);
+
+            if !measured {
+                return Err(Error::last_os_error());
+            }
+            Ok(FontMetrics {
+                dpi: dpi as u32,
+                average_char_width: ((size.cx / 26 + 1) / 2) as u32,
+            })
+        }
+    }
+}
+
+/// The DPI of the window, `GetDpiForWindow` is resolved at runtime as it
+/// is missing before Windows 10 version 1607.
+#[cfg(windows)]
+unsafe fn window_dpi(hwnd: HWND) -> Option<i32> {
+    use windows::{
+        core::{s, w},
+        Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress},
+    };
+
+    if hwnd.is_invalid() {
+        return None;
+    }
+    let user32 = GetModuleHandleW(w!("user32.dll")).ok()?;
+    let get_dpi_for_window: unsafe extern "system" fn(HWND) -> u32 =
+        std::mem::transmute(GetProcAddress(user32, s!("GetDpiForWindow"))?);
+    match get_dpi_for_window(hwnd) {
+        0 => None,
+        dpi => Some(dpi as i32),
+    }
+}
+
+#[cfg(not(windows))]
+impl FontMetrics {
+    pub fn system() -> Result<FontMetrics, Error> {
+        Ok(FontMetrics {
+            dpi: 96,
+            average_char_width: 7,
+        })
+    }
+
+    pub fn for_window(_parent: HWND) -> Result<FontMetrics, Error> {
+        FontMetrics::system()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::*;
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use std::io::{Error, ErrorKind};
+
+impl TaskDialogConfig {
+    /** Use the window of a GUI toolkit, such as a winit window, as the
+    owner window of the dialog. Set `position_relative` to center the dialog
+    on the window instead of the monitor, which sets
+    `TDF_POSITION_RELATIVE_TO_WINDOW`.
+
+    Returns an error when the window is not a Win32 window, or when its
+    handle is not available, e.g. before the window is created. */
+    pub fn set_parent_window<W: HasWindowHandle + ?Sized>(
+        &mut self,
+        window: &W,
+        position_relative: bool,
+    ) -> Result<(), Error> {
+        let handle = window
+            .window_handle()
+            .map_err(|err| Error::other(format!("the window handle is not available: {}", err)))?;
+        match handle.as_raw() {
+            RawWindowHandle::Win32(handle) => {
+                self.parent = HWND(handle.hwnd.get() as _);
+            }
+            handle => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("the parent window is not a Win32 window: {:?}", handle),
+                ))
+            }
+        }
+        if position_relative {
+            self.flags |= TDF_POSITION_RELATIVE_TO_WINDOW;
+        }
+        Ok(())
+    }
+}